@@ -0,0 +1,235 @@
+use app_utility::core::{
+    capture::{Rectangle, Screenshots},
+    config::Config,
+    error::AppError,
+    export::{self, build_default_name},
+    mock::MockBackend,
+    timer::Timer,
+};
+use clap::{Parser, Subcommand, ValueEnum};
+use std::{
+    fs,
+    path::PathBuf,
+    str::FromStr,
+    thread,
+    time::{Duration, Instant},
+};
+
+#[derive(Parser)]
+#[command(version, about = "Take, annotate and save screenshots")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Image or project (.shotproj) to open in the editor
+    #[arg(value_name = "FILE")]
+    pub file: Option<PathBuf>,
+
+    /// Where the screenshots are taken from
+    #[arg(long, global = true, value_enum, default_value_t = Backend::Screens)]
+    backend: Backend,
+
+    /// Image served as a screen by the mock backend, can be repeated for more screens
+    #[arg(long, global = true, value_name = "FILE")]
+    mock_image: Vec<PathBuf>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Backend {
+    /// The screens of the system
+    Screens,
+    /// Synthetic images or the given --mock-image files, for running without a display
+    Mock,
+}
+
+impl Cli {
+    // Builds the capture backend chosen on the command line
+    pub fn screenshots(&self) -> Result<Screenshots, String> {
+        let screenshots = if self.backend == Backend::Mock || !self.mock_image.is_empty() {
+            let backend = if self.mock_image.is_empty() {
+                MockBackend::default()
+            } else {
+                MockBackend::from_files(&self.mock_image)?
+            };
+            Screenshots::with_backend(Box::new(backend))
+        } else {
+            Screenshots::try_new()
+        };
+        screenshots.map_err(|err| format!("unable to list the screens: {}", err))
+    }
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Take a screenshot without opening the window and save it
+    Capture {
+        /// Number of the screen to capture, as listed in the settings (starting from 0)
+        #[arg(long)]
+        screen: Option<usize>,
+
+        /// Capture every screen in a single image, as they are placed on the desktop
+        #[arg(long, conflicts_with_all = ["screen", "area"])]
+        all_screens: bool,
+
+        /// Capture only this area of the screen, given as x,y,width,height in physical pixels
+        /// from the top left corner of the screen
+        #[arg(long, value_name = "X,Y,W,H")]
+        area: Option<Area>,
+
+        /// Seconds to wait before taking the screenshot
+        #[arg(long, default_value_t = 0)]
+        delay: usize,
+
+        /// File to write, its extension selects the format (default: the saving path of the settings)
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Clone, Copy)]
+pub struct Area {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+}
+
+impl FromStr for Area {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let values: Vec<&str> = s.split(',').map(str::trim).collect();
+        if values.len() != 4 {
+            return Err("the area must be given as x,y,width,height".to_string());
+        }
+        let area = Area {
+            x: values[0]
+                .parse()
+                .map_err(|_| format!("invalid x \"{}\"", values[0]))?,
+            y: values[1]
+                .parse()
+                .map_err(|_| format!("invalid y \"{}\"", values[1]))?,
+            width: values[2]
+                .parse()
+                .map_err(|_| format!("invalid width \"{}\"", values[2]))?,
+            height: values[3]
+                .parse()
+                .map_err(|_| format!("invalid height \"{}\"", values[3]))?,
+        };
+        if area.width == 0 || area.height == 0 {
+            return Err("the area must have a positive width and height".to_string());
+        }
+        Ok(area)
+    }
+}
+
+// Runs the command, returning the path of the written file
+pub fn run(command: Command, screenshots: Screenshots) -> Result<PathBuf, String> {
+    match command {
+        Command::Capture {
+            screen,
+            all_screens,
+            area,
+            delay,
+            output,
+        } => capture(screenshots, screen, all_screens, area, delay, output),
+    }
+}
+
+fn capture(
+    mut screenshots: Screenshots,
+    screen: Option<usize>,
+    all_screens: bool,
+    area: Option<Area>,
+    delay: usize,
+    output: Option<PathBuf>,
+) -> Result<PathBuf, String> {
+    // The settings saved by the application provide the defaults. Like the window, the
+    // capture goes on with the default ones if they cannot be loaded
+    let config = Config::load().unwrap_or_else(|err| {
+        log::warn!(
+            "the settings could not be loaded, the default ones are used instead: {}",
+            err
+        );
+        None
+    });
+
+    screenshots.screen_number = match screen {
+        Some(screen) => screen,
+        None => config
+            .as_ref()
+            .map(|config| config.screen_number)
+            .filter(|screen| *screen < screenshots.total_screens())
+            .unwrap_or(screenshots.default_screen_number),
+    };
+    // The screen given on the command line wins over all of them in the settings
+    screenshots.all_screens = all_screens
+        || (screen.is_none() && config.as_ref().is_some_and(|config| config.all_screens));
+    if screenshots.screen_number >= screenshots.total_screens() {
+        return Err(format!(
+            "screen {} does not exist, {} screen(s) available",
+            screenshots.screen_number,
+            screenshots.total_screens()
+        ));
+    }
+
+    let mut output = match output {
+        Some(output) => output,
+        None => {
+            let mut dir = std::env::current_dir().map_err(|err| err.to_string())?;
+            dir.push(
+                config
+                    .as_ref()
+                    .map(|config| config.default_path.as_str())
+                    .unwrap_or("screenshots"),
+            );
+            fs::create_dir_all(&dir)
+                .map_err(|err| format!("unable to create {}: {}", dir.display(), err))?;
+            dir.push(format!("{}.png", build_default_name()));
+            dir
+        }
+    };
+    if output.extension().is_none() {
+        output.set_extension("png");
+    }
+
+    wait(delay);
+
+    let image = screenshots
+        .capture(area.map(|area| Rectangle {
+            x: area.x as f32,
+            y: area.y as f32,
+            width: area.width as f32,
+            height: area.height as f32,
+        }))
+        .map_err(|err| err.to_string())?;
+
+    let export_settings = config.map(|config| config.export).unwrap_or_default();
+    export::save(&image, &output, &export_settings)
+        .map_err(|err| AppError::export(&output, err).to_string())?;
+    Ok(output)
+}
+
+// Same countdown as the timer of the window, one second at a time
+fn wait(delay: usize) {
+    let mut timer = Timer::new();
+    timer.seconds = delay;
+    if timer.seconds == 0 {
+        return;
+    }
+    timer.start_timer();
+    while timer.is_running() {
+        thread::sleep(Duration::from_millis(50));
+        let Some(start) = timer.start_instant else {
+            break;
+        };
+        let now = Instant::now();
+        if now.duration_since(start).as_secs_f32() >= 1.0 {
+            timer.decrement_timer();
+            timer.start_instant = Some(now);
+            if timer.seconds == 0 {
+                timer.reset_timer();
+            }
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Action {
+    Capture,
+    Copy,
+    Close,
+    HomePage,
+    ManageTimer,
+    Modify,
+    NewScreenshot,
+    Open,
+    Redo,
+    ResetTimer,
+    Save,
+    SelectAllScreens,
+    SelectArea,
+    SelectFullscreen,
+    Settings,
+    SetTimer,
+    StartTimer,
+    Undo,
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Action::Capture => "Capture",
+            Action::Close => "Close",
+            Action::Copy => "Copy",
+            Action::HomePage => "HomePage",
+            Action::ManageTimer => "ManageTimer",
+            Action::Modify => "Modify",
+            Action::NewScreenshot => "NewScreenshot",
+            Action::Open => "Open",
+            Action::Redo => "Redo",
+            Action::ResetTimer => "ResetTimer",
+            Action::Save => "Save",
+            Action::SelectAllScreens => "SelectAllScreens",
+            Action::SelectArea => "SelectArea",
+            Action::SelectFullscreen => "SelectFullscreen",
+            Action::SetTimer => "SetTimer",
+            Action::StartTimer => "StartTimer",
+            Action::Settings => "Settings",
+            Action::Undo => "Undo",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Action {
+    pub fn can_be_performed_during_image_view(self) -> bool {
+        match self {
+            Action::Capture => false,
+            Action::Close => true,
+            Action::Copy => true,
+            Action::HomePage => false,
+            Action::ManageTimer => false,
+            Action::Modify => true,
+            Action::NewScreenshot => true,
+            Action::Open => false,
+            Action::Redo => true,
+            Action::ResetTimer => false,
+            Action::Save => true,
+            Action::SelectAllScreens => false,
+            Action::SelectArea => false,
+            Action::SelectFullscreen => false,
+            Action::Settings => false,
+            Action::SetTimer => false,
+            Action::StartTimer => false,
+            Action::Undo => true,
+        }
+    }
+}
@@ -0,0 +1,576 @@
+use super::text::TextStyle;
+use serde::{Deserialize, Serialize};
+use std::ops::{Add, Mul, Neg, Sub};
+
+// Position in the pixels of the screenshot
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Point {
+    pub fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    pub fn length(self) -> f32 {
+        self.x.hypot(self.y)
+    }
+
+    pub fn distance(self, other: Point) -> f32 {
+        (self - other).length()
+    }
+
+    pub fn distance_to_segment(self, a: Point, b: Point) -> f32 {
+        let ab = b - a;
+        let length_sq = ab.dot(ab);
+        if length_sq == 0.0 {
+            return self.distance(a);
+        }
+        let t = ((self - a).dot(ab) / length_sq).clamp(0.0, 1.0);
+        self.distance(a + ab * t)
+    }
+
+    pub fn dot(self, other: Point) -> f32 {
+        self.x * other.x + self.y * other.y
+    }
+
+    pub fn normalized(self) -> Point {
+        let length = self.length();
+        if length == 0.0 {
+            self
+        } else {
+            self * (1.0 / length)
+        }
+    }
+
+    pub fn rotated(self, angle: f32) -> Point {
+        let (sin, cos) = angle.sin_cos();
+        Point::new(self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+    }
+
+    pub fn min(self, other: Point) -> Point {
+        Point::new(self.x.min(other.x), self.y.min(other.y))
+    }
+
+    pub fn max(self, other: Point) -> Point {
+        Point::new(self.x.max(other.x), self.y.max(other.y))
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, other: Point) -> Point {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Neg for Point {
+    type Output = Point;
+
+    fn neg(self) -> Point {
+        Point::new(-self.x, -self.y)
+    }
+}
+
+impl Mul<f32> for Point {
+    type Output = Point;
+
+    fn mul(self, factor: f32) -> Point {
+        Point::new(self.x * factor, self.y * factor)
+    }
+}
+
+// sRGB color with unmultiplied alpha
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color {
+    pub const BLACK: Color = Color::rgb(0, 0, 0);
+    pub const TRANSPARENT: Color = Color::rgba(0, 0, 0, 0);
+
+    // Colors of the highlighter, translucent so that they tint what is under them
+    pub const HIGHLIGHTER_PALETTE: [(&'static str, Color); 3] = [
+        ("Yellow", Color::rgba(255, 230, 0, 200)),
+        ("Green", Color::rgba(80, 230, 80, 200)),
+        ("Pink", Color::rgba(255, 90, 200, 200)),
+    ];
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b, a: 255 }
+    }
+
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+}
+
+// Width is in pixels of the screenshot
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Stroke {
+    pub width: f32,
+    pub color: Color,
+}
+
+impl Stroke {
+    pub fn new(width: f32, color: Color) -> Self {
+        Self { width, color }
+    }
+}
+
+// How a redacted area hides the pixels under it
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Redaction {
+    // Standard deviation of the Gaussian blur, in pixels of the screenshot
+    Blur(f32),
+    // Side of the blocks, in pixels of the screenshot
+    Pixelate(u32),
+    Fill(Color),
+}
+
+impl Redaction {
+    pub fn name(self) -> &'static str {
+        match self {
+            Redaction::Blur(_) => "Blur",
+            Redaction::Pixelate(_) => "Pixelate",
+            Redaction::Fill(_) => "Fill",
+        }
+    }
+}
+
+// How a line or an arrow is drawn along its length
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Dash {
+    Solid,
+    Dashed,
+    Dotted,
+}
+
+impl Dash {
+    pub fn all() -> [Dash; 3] {
+        [Dash::Solid, Dash::Dashed, Dash::Dotted]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Dash::Solid => "Solid",
+            Dash::Dashed => "Dashed",
+            Dash::Dotted => "Dotted",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum ArrowHead {
+    None,
+    Open,
+    Filled,
+}
+
+impl ArrowHead {
+    pub fn all() -> [ArrowHead; 3] {
+        [ArrowHead::Open, ArrowHead::Filled, ArrowHead::None]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ArrowHead::None => "None",
+            ArrowHead::Open => "Open",
+            ArrowHead::Filled => "Filled",
+        }
+    }
+}
+
+// The heads of an arrow. `size` is the length of their sides in pixels of the screenshot,
+// whatever the width of the stroke
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ArrowStyle {
+    pub head: ArrowHead,
+    pub both_ends: bool,
+    pub size: f32,
+}
+
+impl Default for ArrowStyle {
+    fn default() -> Self {
+        Self {
+            head: ArrowHead::Open,
+            both_ends: false,
+            size: 20.0,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Shape {
+    Pen {
+        points: Vec<Point>,
+        stroke: Stroke,
+    },
+    // Multiplies the colors under it by the color of the stroke
+    Highlight {
+        points: Vec<Point>,
+        stroke: Stroke,
+    },
+    Line {
+        start: Point,
+        end: Point,
+        stroke: Stroke,
+        dash: Dash,
+    },
+    // A curved arrow is bent towards its `control` point
+    Arrow {
+        start: Point,
+        end: Point,
+        control: Option<Point>,
+        stroke: Stroke,
+        dash: Dash,
+        style: ArrowStyle,
+    },
+    // `rounding` is the radius of the corners
+    Rect {
+        start: Point,
+        end: Point,
+        stroke: Stroke,
+        fill: Color,
+        rounding: f32,
+    },
+    Circle {
+        center: Point,
+        radius: f32,
+        stroke: Stroke,
+        fill: Color,
+    },
+    // The ellipse inside the rectangle between `start` and `end`
+    Ellipse {
+        start: Point,
+        end: Point,
+        stroke: Stroke,
+        fill: Color,
+    },
+    // `pos` is the top left corner of the box around the text
+    Text {
+        pos: Point,
+        text: String,
+        style: TextStyle,
+    },
+    Redact {
+        start: Point,
+        end: Point,
+        redaction: Redaction,
+    },
+    // A numbered badge, its label depends on how many markers come before it
+    Marker {
+        center: Point,
+        radius: f32,
+        color: Color,
+    },
+}
+
+impl Shape {
+    pub fn translate(&mut self, offset: Point) {
+        match self {
+            Shape::Pen { points, .. } | Shape::Highlight { points, .. } => {
+                for point in points.iter_mut() {
+                    *point = *point + offset;
+                }
+            }
+            Shape::Line { start, end, .. }
+            | Shape::Rect { start, end, .. }
+            | Shape::Ellipse { start, end, .. }
+            | Shape::Redact { start, end, .. } => {
+                *start = *start + offset;
+                *end = *end + offset;
+            }
+            Shape::Arrow {
+                start,
+                end,
+                control,
+                ..
+            } => {
+                *start = *start + offset;
+                *end = *end + offset;
+                if let Some(control) = control {
+                    *control = *control + offset;
+                }
+            }
+            Shape::Circle { center, .. } | Shape::Marker { center, .. } => {
+                *center = *center + offset
+            }
+            Shape::Text { pos, .. } => *pos = *pos + offset,
+        }
+    }
+
+    // The text, the markers and the redactions have no width, the color of a redaction is
+    // the one it is filled with
+    pub fn stroke(&self) -> Stroke {
+        match self {
+            Shape::Pen { stroke, .. }
+            | Shape::Highlight { stroke, .. }
+            | Shape::Line { stroke, .. }
+            | Shape::Arrow { stroke, .. }
+            | Shape::Rect { stroke, .. }
+            | Shape::Circle { stroke, .. }
+            | Shape::Ellipse { stroke, .. } => *stroke,
+            Shape::Text { style, .. } => Stroke::new(0.0, style.color),
+            Shape::Marker { color, .. } => Stroke::new(0.0, *color),
+            Shape::Redact { redaction, .. } => match redaction {
+                Redaction::Fill(color) => Stroke::new(0.0, *color),
+                _ => Stroke::new(0.0, Color::BLACK),
+            },
+        }
+    }
+
+    pub fn set_stroke(&mut self, new_stroke: Stroke) {
+        match self {
+            Shape::Pen { stroke, .. }
+            | Shape::Highlight { stroke, .. }
+            | Shape::Line { stroke, .. }
+            | Shape::Arrow { stroke, .. }
+            | Shape::Rect { stroke, .. }
+            | Shape::Circle { stroke, .. }
+            | Shape::Ellipse { stroke, .. } => *stroke = new_stroke,
+            Shape::Text { style, .. } => style.color = new_stroke.color,
+            Shape::Marker { color, .. } => *color = new_stroke.color,
+            Shape::Redact { redaction, .. } => {
+                if let Redaction::Fill(color) = redaction {
+                    *color = new_stroke.color;
+                }
+            }
+        }
+    }
+
+    // The fill and the corner radius, for the shapes that have them. The fill of a text is
+    // its background box
+    pub fn fill(&self) -> Option<(Color, f32)> {
+        match self {
+            Shape::Rect { fill, rounding, .. } => Some((*fill, *rounding)),
+            Shape::Text { style, .. } => Some((style.background, 0.0)),
+            Shape::Circle { fill, .. } | Shape::Ellipse { fill, .. } => Some((*fill, 0.0)),
+            _ => None,
+        }
+    }
+
+    pub fn set_fill(&mut self, new_fill: Color, new_rounding: f32) {
+        match self {
+            Shape::Rect { fill, rounding, .. } => {
+                *fill = new_fill;
+                *rounding = new_rounding;
+            }
+            Shape::Circle { fill, .. } | Shape::Ellipse { fill, .. } => *fill = new_fill,
+            Shape::Text { style, .. } => style.background = new_fill,
+            _ => {}
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum MarkerSequence {
+    Numbers,
+    Letters,
+}
+
+impl MarkerSequence {
+    // The smallest start of the sequence: the numbers can start at 0, the letters at "A"
+    pub fn first(self) -> u32 {
+        match self {
+            MarkerSequence::Numbers => 0,
+            MarkerSequence::Letters => 1,
+        }
+    }
+}
+
+// How the markers are labelled, from the first one to the last one
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Numbering {
+    pub sequence: MarkerSequence,
+    // The label of the first marker: 1 is "1" or "A", 0 is "0" and only for the numbers
+    pub start: u32,
+}
+
+impl Default for Numbering {
+    fn default() -> Self {
+        Self {
+            sequence: MarkerSequence::Numbers,
+            start: 1,
+        }
+    }
+}
+
+impl Numbering {
+    // Whether the sequence has a label for the start
+    pub fn is_valid(self) -> bool {
+        self.start >= self.sequence.first()
+    }
+
+    // The label of the marker with `index` markers before it
+    pub fn label(self, index: usize) -> String {
+        let value = self.start as usize + index;
+        match self.sequence {
+            MarkerSequence::Numbers => value.to_string(),
+            MarkerSequence::Letters => letters(value),
+        }
+    }
+}
+
+// A, B, ..., Z, AA, AB, ... like the columns of a spreadsheet, 1 is "A"
+fn letters(mut value: usize) -> String {
+    let mut label = Vec::new();
+    while value > 0 {
+        value -= 1;
+        label.push(b'A' + (value % 26) as u8);
+        value /= 26;
+    }
+    label.reverse();
+    String::from_utf8(label).unwrap()
+}
+
+// Signed distance from the point to the border of the rectangle with rounded corners,
+// negative inside of it
+pub fn rounded_rect_distance(point: Point, min: Point, max: Point, rounding: f32) -> f32 {
+    let half = (max - min) * 0.5;
+    let rounding = rounding.clamp(0.0, half.x.min(half.y));
+    let center = min + half;
+    let q = Point::new(
+        (point.x - center.x).abs() - half.x + rounding,
+        (point.y - center.y).abs() - half.y + rounding,
+    );
+    q.max(Point::default()).length() + q.x.max(q.y).min(0.0) - rounding
+}
+
+// Approximate signed distance from the point to the border of the ellipse inside the
+// rectangle, negative inside of it
+pub fn ellipse_distance(point: Point, min: Point, max: Point) -> f32 {
+    let radii = (max - min) * 0.5;
+    if radii.x <= 0.0 || radii.y <= 0.0 {
+        // A flat ellipse is a segment
+        return point.distance_to_segment(min, max);
+    }
+    let p = point - (min + radii);
+    let f = (p.x / radii.x).powi(2) + (p.y / radii.y).powi(2) - 1.0;
+    let gradient = Point::new(
+        2.0 * p.x / (radii.x * radii.x),
+        2.0 * p.y / (radii.y * radii.y),
+    );
+    if gradient.length() == 0.0 {
+        return -radii.x.min(radii.y);
+    }
+    f / gradient.length()
+}
+
+// Identifies an annotation for as long as the screenshot is edited
+pub type ShapeId = u64;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Annotation {
+    pub id: ShapeId,
+    pub shape: Shape,
+}
+
+// The annotations drawn on the screenshot, from the bottom to the top
+#[derive(Clone, Default, Debug)]
+pub struct Annotations {
+    items: Vec<Annotation>,
+    next_id: ShapeId,
+    pub numbering: Numbering,
+}
+
+impl Annotations {
+    pub fn items(&self) -> &[Annotation] {
+        &self.items
+    }
+
+    pub fn shapes(&self) -> impl Iterator<Item = &Shape> {
+        self.items.iter().map(|annotation| &annotation.shape)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn get(&self, id: ShapeId) -> Option<&Shape> {
+        self.items
+            .iter()
+            .find(|annotation| annotation.id == id)
+            .map(|annotation| &annotation.shape)
+    }
+
+    pub fn get_mut(&mut self, id: ShapeId) -> Option<&mut Shape> {
+        self.items
+            .iter_mut()
+            .find(|annotation| annotation.id == id)
+            .map(|annotation| &mut annotation.shape)
+    }
+
+    // Gives an id to a new annotation, without adding it yet
+    pub fn create(&mut self, shape: Shape) -> Annotation {
+        self.next_id += 1;
+        Annotation {
+            id: self.next_id,
+            shape,
+        }
+    }
+
+    pub fn insert(&mut self, index: usize, annotation: Annotation) {
+        let index = index.min(self.items.len());
+        self.items.insert(index, annotation);
+    }
+
+    // Returns the removed annotation with the position it had
+    pub fn remove(&mut self, id: ShapeId) -> Option<(usize, Annotation)> {
+        let index = self
+            .items
+            .iter()
+            .position(|annotation| annotation.id == id)?;
+        Some((index, self.items.remove(index)))
+    }
+
+    // The label of the marker with the given id. The markers are numbered in the order
+    // they were added, so removing one renumbers the following ones
+    pub fn marker_label(&self, id: ShapeId) -> Option<String> {
+        let mut markers = self
+            .items
+            .iter()
+            .filter(|annotation| matches!(annotation.shape, Shape::Marker { .. }));
+        let index = markers.position(|annotation| annotation.id == id)?;
+        Some(self.numbering.label(index))
+    }
+
+    // The label the next marker gets
+    pub fn next_marker_label(&self) -> String {
+        let markers = self
+            .shapes()
+            .filter(|shape| matches!(shape, Shape::Marker { .. }))
+            .count();
+        self.numbering.label(markers)
+    }
+
+    pub fn translate(&mut self, offset: Point) {
+        for annotation in self.items.iter_mut() {
+            annotation.shape.translate(offset);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+}
@@ -0,0 +1,413 @@
+use super::{
+    annotation::{Color, Point},
+    crop,
+    error::AppError,
+    scaling,
+};
+use image::{imageops, RgbaImage};
+use screenshots::Screen;
+use serde::{Deserialize, Serialize};
+
+// An area, whose unit and origin depend on where it is used: physical pixels from the top
+// left corner of a screen or of the screenshot for the backends and the crop, points of the
+// desktop for `Session::area`, the frozen screens and `scaling::area_to_pixels`
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct Rectangle {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+// Position and size of a screen on the desktop, in points, as reported by the system
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ScreenInfo {
+    pub id: u32,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub scale_factor: f32,
+    pub is_primary: bool,
+}
+
+impl ScreenInfo {
+    // The screen on the desktop, in points
+    pub fn bounds(&self) -> Rectangle {
+        Rectangle {
+            x: self.x as f32,
+            y: self.y as f32,
+            width: self.width as f32,
+            height: self.height as f32,
+        }
+    }
+
+    // An area of the desktop in points, in the physical pixels of the screen it covers
+    pub fn to_physical(&self, area: Rectangle) -> Rectangle {
+        scaling::area_to_pixels(area, self.origin(), self.scale_factor)
+    }
+
+    fn origin(&self) -> Point {
+        Point::new(self.x as f32, self.y as f32)
+    }
+}
+
+// The part of `a` that is also in `b`, `None` if they do not overlap
+fn intersection(a: Rectangle, b: Rectangle) -> Option<Rectangle> {
+    let (x, y) = (a.x.max(b.x), a.y.max(b.y));
+    let width = (a.x + a.width).min(b.x + b.width) - x;
+    let height = (a.y + a.height).min(b.y + b.height) - y;
+    (width > 0.0 && height > 0.0).then_some(Rectangle {
+        x,
+        y,
+        width,
+        height,
+    })
+}
+
+// Source of the screenshots: the real screens of the system or a fake set of screens. The
+// areas are in whole physical pixels of the screen
+pub trait CaptureBackend {
+    fn screens(&self) -> Result<Vec<ScreenInfo>, String>;
+    fn capture(&self, screen: usize) -> Result<RgbaImage, String>;
+    fn capture_area(&self, screen: usize, area: Rectangle) -> Result<RgbaImage, String>;
+}
+
+// Captures the screens of the system through the `screenshots` crate
+pub struct ScreenshotsBackend {
+    screens: Vec<Screen>,
+}
+
+impl ScreenshotsBackend {
+    pub fn try_new() -> Result<Self, String> {
+        Ok(Self {
+            screens: Screen::all().map_err(|err| err.to_string())?,
+        })
+    }
+
+    fn screen(&self, screen: usize) -> Result<&Screen, String> {
+        self.screens
+            .get(screen)
+            .ok_or_else(|| format!("there is no screen {}", screen))
+    }
+}
+
+impl CaptureBackend for ScreenshotsBackend {
+    fn screens(&self) -> Result<Vec<ScreenInfo>, String> {
+        Ok(self
+            .screens
+            .iter()
+            .map(|screen| {
+                let info = screen.display_info;
+                ScreenInfo {
+                    id: info.id,
+                    x: info.x,
+                    y: info.y,
+                    width: info.width,
+                    height: info.height,
+                    scale_factor: info.scale_factor,
+                    is_primary: info.is_primary,
+                }
+            })
+            .collect())
+    }
+
+    fn capture(&self, screen: usize) -> Result<RgbaImage, String> {
+        let image = self
+            .screen(screen)?
+            .capture()
+            .map_err(|err| err.to_string())?;
+        to_rgba_image(image)
+    }
+
+    fn capture_area(&self, screen: usize, area: Rectangle) -> Result<RgbaImage, String> {
+        // The crate takes the area in points and scales it by itself, with a rounding that
+        // differs between the platforms, so the pixels are cropped from the whole screen
+        let image = self.capture(screen)?;
+        let area = crop::clamp(area, image.width(), image.height())
+            .ok_or_else(|| "the area is outside of the screen".to_string())?;
+        Ok(crop::crop_image(&image, Some(area)))
+    }
+}
+
+fn to_rgba_image(image: screenshots::Image) -> Result<RgbaImage, String> {
+    RgbaImage::from_raw(image.width(), image.height(), image.rgba().clone())
+        .ok_or_else(|| "the captured image has an invalid size".to_string())
+}
+
+pub struct Screenshots {
+    backend: Box<dyn CaptureBackend>,
+    screens: Vec<ScreenInfo>,
+    pub screen_number: usize,
+    // The full screen shots capture every screen in place of the selected one
+    pub all_screens: bool,
+    pub default_screen_number: usize,
+}
+
+impl Screenshots {
+    // Uses the screens of the system
+    pub fn try_new() -> Result<Self, String> {
+        Self::with_backend(Box::new(ScreenshotsBackend::try_new()?))
+    }
+
+    pub fn with_backend(backend: Box<dyn CaptureBackend>) -> Result<Self, String> {
+        let screens = backend.screens()?;
+        if screens.is_empty() {
+            return Err("no screen was found".to_string());
+        }
+        Ok(Self {
+            backend,
+            screens,
+            screen_number: 0,
+            all_screens: false,
+            default_screen_number: 0,
+        })
+    }
+
+    pub fn get_screen(&self) -> ScreenInfo {
+        self.screens[self.screen_number]
+    }
+
+    pub fn total_screens(&self) -> usize {
+        self.screens.len()
+    }
+
+    // The box around every screen, in points
+    pub fn desktop(&self) -> Rectangle {
+        bounding_box(self.screens.iter().map(ScreenInfo::bounds))
+    }
+
+    // Captures the selected screen, or only the given area of it
+    pub fn capture(&self, area: Option<Rectangle>) -> Result<RgbaImage, AppError> {
+        if self.all_screens && area.is_none() {
+            return self.capture_all();
+        }
+        match area {
+            Some(area) => self.backend.capture_area(self.screen_number, area),
+            None => self.backend.capture(self.screen_number),
+        }
+        .map_err(|message| AppError::Capture {
+            screen: self.screen_number,
+            area,
+            message,
+        })
+    }
+
+    // Captures every screen, placed as they are on the desktop
+    pub fn capture_all(&self) -> Result<RgbaImage, AppError> {
+        Ok(composite(&self.freeze()?.screens))
+    }
+
+    // Captures every screen as it is now, so that an area of the desktop can be selected
+    // on it afterwards
+    pub fn freeze(&self) -> Result<FrozenDesktop, AppError> {
+        let mut screens = Vec::new();
+        for (screen, info) in self.screens.iter().enumerate() {
+            let image = self
+                .backend
+                .capture(screen)
+                .map_err(|message| AppError::Capture {
+                    screen,
+                    area: None,
+                    message,
+                })?;
+            screens.push((*info, image));
+        }
+        Ok(FrozenDesktop { screens })
+    }
+}
+
+// The screens captured at once, with their position on the desktop
+pub struct FrozenDesktop {
+    pub screens: Vec<(ScreenInfo, RgbaImage)>,
+}
+
+// A pixel of the frozen screens
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Pixel {
+    pub screen: usize,
+    // In the image of the screen
+    pub x: u32,
+    pub y: u32,
+    // On the desktop, counted in pixels of the screen from the top left corner of the
+    // desktop
+    pub absolute: (i32, i32),
+    pub color: Color,
+}
+
+impl FrozenDesktop {
+    // An area of the desktop, in points, cut from the screens. It can be on any screen or
+    // span several of them. `None` if it is on none of them
+    pub fn crop(&self, area: Rectangle) -> Option<RgbaImage> {
+        let mut parts: Vec<_> = self
+            .split(area)
+            .map(|(part, info, image, pixels)| {
+                (
+                    part,
+                    info.scale_factor,
+                    crop::crop_image(image, Some(pixels)),
+                )
+            })
+            .collect();
+        match parts.len() {
+            0 => None,
+            // The pixels of a single screen are kept as they are
+            1 => Some(parts.remove(0).2),
+            _ => Some(compose(
+                &parts
+                    .iter()
+                    .map(|(part, scale, image)| (*part, *scale, image))
+                    .collect::<Vec<_>>(),
+            )),
+        }
+    }
+
+    // The size in pixels of the image `crop` cuts for the area, without cutting it
+    pub fn size(&self, area: Rectangle) -> Option<(u32, u32)> {
+        let parts: Vec<_> = self.split(area).collect();
+        let size = match parts.as_slice() {
+            [] => return None,
+            [(_, _, _, pixels)] => *pixels,
+            _ => {
+                let scale = parts.iter().fold(0.0f32, |scale, (_, info, _, _)| {
+                    scale.max(info.scale_factor)
+                });
+                let bounds = bounding_box(parts.iter().map(|(part, _, _, _)| *part));
+                scaling::area_to_pixels(bounds, Point::new(bounds.x, bounds.y), scale)
+            }
+        };
+        Some((size.width as u32, size.height as u32))
+    }
+
+    // The pixel under a point of the desktop, `None` outside of the screens
+    pub fn pixel_at(&self, point: Point) -> Option<Pixel> {
+        let (screen, (info, image)) = self.screen_at(point)?;
+        let scale = info.scale_factor;
+        let x = scaling::to_pixels(point.x - info.x as f32, scale).floor() as u32;
+        let y = scaling::to_pixels(point.y - info.y as f32, scale).floor() as u32;
+        let [r, g, b, a] = image.get_pixel_checked(x, y)?.0;
+        Some(Pixel {
+            screen,
+            x,
+            y,
+            absolute: (
+                scaling::to_pixels(info.x as f32, scale).round() as i32 + x as i32,
+                scaling::to_pixels(info.y as f32, scale).round() as i32 + y as i32,
+            ),
+            color: Color::rgba(r, g, b, a),
+        })
+    }
+
+    // A point of the desktop moved onto the nearest boundary between the pixels of the
+    // screen it is on, and then by whole pixels of that screen. The right and bottom edges
+    // of a screen are on it, and outside of the screens a point is a pixel
+    pub fn nudge(&self, point: Point, pixels: Point) -> Point {
+        let (origin, scale) = self
+            .screens
+            .iter()
+            .find(|(info, _)| {
+                let bounds = info.bounds();
+                point.x >= bounds.x
+                    && point.y >= bounds.y
+                    && point.x <= bounds.x + bounds.width
+                    && point.y <= bounds.y + bounds.height
+            })
+            .map_or((Point::default(), 1.0), |(info, _)| {
+                (Point::new(info.x as f32, info.y as f32), info.scale_factor)
+            });
+        let snap = |points: f32, origin: f32, pixels: f32| {
+            origin
+                + scaling::to_points(
+                    scaling::to_pixels(points - origin, scale).round() + pixels,
+                    scale,
+                )
+        };
+        Point::new(
+            snap(point.x, origin.x, pixels.x),
+            snap(point.y, origin.y, pixels.y),
+        )
+    }
+
+    fn screen_at(&self, point: Point) -> Option<(usize, &(ScreenInfo, RgbaImage))> {
+        self.screens.iter().enumerate().find(|(_, (info, _))| {
+            let bounds = info.bounds();
+            point.x >= bounds.x
+                && point.y >= bounds.y
+                && point.x < bounds.x + bounds.width
+                && point.y < bounds.y + bounds.height
+        })
+    }
+
+    // The parts of the area on each screen, in points, with the screen, its image and the
+    // part in the pixels of the image
+    fn split(
+        &self,
+        area: Rectangle,
+    ) -> impl Iterator<Item = (Rectangle, &ScreenInfo, &RgbaImage, Rectangle)> {
+        self.screens.iter().filter_map(move |(info, image)| {
+            let part = intersection(info.bounds(), area)?;
+            let pixels = crop::clamp(info.to_physical(part), image.width(), image.height())?;
+            Some((part, info, image, pixels))
+        })
+    }
+}
+
+// The screens in a single image, at the position they have on the desktop
+pub fn composite(screens: &[(ScreenInfo, RgbaImage)]) -> RgbaImage {
+    let parts: Vec<_> = screens
+        .iter()
+        .map(|(info, image)| (info.bounds(), info.scale_factor, image))
+        .collect();
+    compose(&parts)
+}
+
+fn bounding_box(areas: impl Iterator<Item = Rectangle>) -> Rectangle {
+    let (min_x, min_y, max_x, max_y) = areas.fold(
+        (
+            f32::INFINITY,
+            f32::INFINITY,
+            f32::NEG_INFINITY,
+            f32::NEG_INFINITY,
+        ),
+        |(min_x, min_y, max_x, max_y), area| {
+            (
+                min_x.min(area.x),
+                min_y.min(area.y),
+                max_x.max(area.x + area.width),
+                max_y.max(area.y + area.height),
+            )
+        },
+    );
+    Rectangle {
+        x: min_x,
+        y: min_y,
+        width: max_x - min_x,
+        height: max_y - min_y,
+    }
+}
+
+// Images of areas of the desktop, in points, with the scale factor of their pixels, put
+// together. The image has the largest scale factor among them and the ones with a smaller
+// one are enlarged, instead of overlapping or leaving gaps. What no image covers is
+// transparent
+fn compose(parts: &[(Rectangle, f32, &RgbaImage)]) -> RgbaImage {
+    let scale = parts
+        .iter()
+        .fold(0.0f32, |scale, (_, part_scale, _)| scale.max(*part_scale));
+    let bounds = bounding_box(parts.iter().map(|(area, _, _)| *area));
+    let origin = Point::new(bounds.x, bounds.y);
+    let size = scaling::area_to_pixels(bounds, origin, scale);
+    let mut canvas = RgbaImage::new(size.width.max(0.0) as u32, size.height.max(0.0) as u32);
+    for (area, _, image) in parts {
+        let area = scaling::area_to_pixels(*area, origin, scale);
+        let (width, height) = (area.width as u32, area.height as u32);
+        let (x, y) = (area.x as i64, area.y as i64);
+        if image.dimensions() == (width, height) {
+            imageops::replace(&mut canvas, *image, x, y);
+        } else {
+            let image = imageops::resize(*image, width, height, imageops::FilterType::Triangle);
+            imageops::replace(&mut canvas, &image, x, y);
+        }
+    }
+    canvas
+}
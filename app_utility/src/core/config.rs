@@ -0,0 +1,87 @@
+use super::{export::ExportSettings, shortcut::ShortCut};
+use serde::{Deserialize, Serialize};
+use std::{fmt, fs, io, path::PathBuf};
+
+// Bump when the layout of the file changes in a way older versions cannot read
+pub const CONFIG_VERSION: u32 = 1;
+
+const CONFIG_DIR: &str = "app_utility";
+const CONFIG_FILE: &str = "settings.toml";
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Config {
+    pub version: u32,
+    pub default_path: String,
+    // `None` when the screenshots are named after the current date and time
+    pub default_name: Option<String>,
+    pub screen_number: usize,
+    // Added after the first version, older files capture the selected screen
+    #[serde(default)]
+    pub all_screens: bool,
+    pub export: ExportSettings,
+    pub shortcuts: Vec<ShortCut>,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    NoConfigDir,
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, String),
+    Serialize(String),
+    Version(PathBuf, u32),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::NoConfigDir => write!(f, "no configuration directory available"),
+            ConfigError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            ConfigError::Parse(path, err) => {
+                write!(f, "{} is malformed: {}", path.display(), err)
+            }
+            ConfigError::Serialize(err) => write!(f, "unable to serialize the settings: {}", err),
+            ConfigError::Version(path, version) => write!(
+                f,
+                "{} has version {}, but only version {} is supported",
+                path.display(),
+                version,
+                CONFIG_VERSION
+            ),
+        }
+    }
+}
+
+impl Config {
+    pub fn path() -> Result<PathBuf, ConfigError> {
+        let mut path = dirs::config_dir().ok_or(ConfigError::NoConfigDir)?;
+        path.push(CONFIG_DIR);
+        path.push(CONFIG_FILE);
+        Ok(path)
+    }
+
+    // Returns `None` when no settings have been saved yet
+    pub fn load() -> Result<Option<Config>, ConfigError> {
+        let path = Config::path()?;
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(ConfigError::Io(path, err)),
+        };
+        let config: Config = toml::from_str(&content)
+            .map_err(|err| ConfigError::Parse(path.clone(), err.to_string()))?;
+        if config.version != CONFIG_VERSION {
+            return Err(ConfigError::Version(path, config.version));
+        }
+        Ok(Some(config))
+    }
+
+    pub fn save(&self) -> Result<(), ConfigError> {
+        let path = Config::path()?;
+        let content =
+            toml::to_string_pretty(self).map_err(|err| ConfigError::Serialize(err.to_string()))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|err| ConfigError::Io(path.clone(), err))?;
+        }
+        fs::write(&path, content).map_err(|err| ConfigError::Io(path, err))
+    }
+}
@@ -0,0 +1,200 @@
+use super::{annotation::Point, capture::Rectangle, scaling};
+use image::{imageops, RgbaImage};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AspectRatio {
+    Free,
+    Square,
+    Widescreen,
+    Standard,
+}
+
+impl AspectRatio {
+    pub fn all() -> [AspectRatio; 4] {
+        [
+            AspectRatio::Free,
+            AspectRatio::Square,
+            AspectRatio::Widescreen,
+            AspectRatio::Standard,
+        ]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AspectRatio::Free => "Free",
+            AspectRatio::Square => "1:1",
+            AspectRatio::Widescreen => "16:9",
+            AspectRatio::Standard => "4:3",
+        }
+    }
+
+    // Width divided by height
+    pub fn ratio(self) -> Option<f32> {
+        match self {
+            AspectRatio::Free => None,
+            AspectRatio::Square => Some(1.0),
+            AspectRatio::Widescreen => Some(16.0 / 9.0),
+            AspectRatio::Standard => Some(4.0 / 3.0),
+        }
+    }
+}
+
+// The part of the image covered by the area, which is clipped to the image and rounded to
+// whole pixels. `None` if nothing of the image is left
+pub fn clamp(area: Rectangle, width: u32, height: u32) -> Option<Rectangle> {
+    let area = scaling::snap(area);
+    let x0 = area.x.clamp(0.0, width as f32);
+    let y0 = area.y.clamp(0.0, height as f32);
+    let x1 = (area.x + area.width).clamp(0.0, width as f32);
+    let y1 = (area.y + area.height).clamp(0.0, height as f32);
+    if x1 <= x0 || y1 <= y0 {
+        return None;
+    }
+    Some(Rectangle {
+        x: x0,
+        y: y0,
+        width: x1 - x0,
+        height: y1 - y0,
+    })
+}
+
+// The area with one corner dragged to `to` while the opposite corner stays at `fixed`,
+// keeping the aspect ratio and staying inside an image of the given size
+pub fn drag_corner(
+    fixed: Point,
+    to: Point,
+    aspect: AspectRatio,
+    width: u32,
+    height: u32,
+) -> Rectangle {
+    let to = Point::new(
+        to.x.clamp(0.0, width as f32),
+        to.y.clamp(0.0, height as f32),
+    );
+    let mut size = Point::new((to.x - fixed.x).abs(), (to.y - fixed.y).abs());
+    if let Some(ratio) = aspect.ratio() {
+        // The largest area with the ratio that fits where the pointer is and in the image
+        let max_width = if to.x < fixed.x {
+            fixed.x
+        } else {
+            width as f32 - fixed.x
+        };
+        let max_height = if to.y < fixed.y {
+            fixed.y
+        } else {
+            height as f32 - fixed.y
+        };
+        let area_width = size
+            .x
+            .max(size.y * ratio)
+            .min(max_width)
+            .min(max_height * ratio);
+        size = Point::new(area_width, area_width / ratio);
+    }
+    let x = if to.x < fixed.x {
+        fixed.x - size.x
+    } else {
+        fixed.x
+    };
+    let y = if to.y < fixed.y {
+        fixed.y - size.y
+    } else {
+        fixed.y
+    };
+    Rectangle {
+        x,
+        y,
+        width: size.x,
+        height: size.y,
+    }
+}
+
+// The area moved by `offset`, without leaving the image
+pub fn move_by(area: Rectangle, offset: Point, width: u32, height: u32) -> Rectangle {
+    Rectangle {
+        x: (area.x + offset.x).clamp(0.0, (width as f32 - area.width).max(0.0)),
+        y: (area.y + offset.y).clamp(0.0, (height as f32 - area.height).max(0.0)),
+        ..area
+    }
+}
+
+// The area with the given width and, if the ratio is fixed, the matching height. The top
+// left corner stays where it is
+pub fn with_width(
+    area: Rectangle,
+    area_width: f32,
+    aspect: AspectRatio,
+    width: u32,
+    height: u32,
+) -> Rectangle {
+    let bottom_right = Point::new(
+        area.x + area_width,
+        area.y
+            + aspect
+                .ratio()
+                .map_or(area.height, |ratio| area_width / ratio),
+    );
+    drag_corner(
+        Point::new(area.x, area.y),
+        bottom_right,
+        aspect,
+        width,
+        height,
+    )
+}
+
+// The area with the given height and, if the ratio is fixed, the matching width
+pub fn with_height(
+    area: Rectangle,
+    area_height: f32,
+    aspect: AspectRatio,
+    width: u32,
+    height: u32,
+) -> Rectangle {
+    let area_width = aspect
+        .ratio()
+        .map_or(area.width, |ratio| area_height * ratio);
+    let bottom_right = Point::new(area.x + area_width, area.y + area_height);
+    drag_corner(
+        Point::new(area.x, area.y),
+        bottom_right,
+        aspect,
+        width,
+        height,
+    )
+}
+
+// The largest area with the ratio inside the given one, around the same center
+pub fn fit(area: Rectangle, aspect: AspectRatio) -> Rectangle {
+    let Some(ratio) = aspect.ratio() else {
+        return area;
+    };
+    let area_width = area.width.min(area.height * ratio);
+    let area_height = area_width / ratio;
+    Rectangle {
+        x: area.x + (area.width - area_width) / 2.0,
+        y: area.y + (area.height - area_height) / 2.0,
+        width: area_width,
+        height: area_height,
+    }
+}
+
+// The pixels of the original screenshot inside the crop area
+pub fn crop_image(original: &RgbaImage, area: Option<Rectangle>) -> RgbaImage {
+    match area {
+        Some(area) => imageops::crop_imm(
+            original,
+            area.x as u32,
+            area.y as u32,
+            area.width as u32,
+            area.height as u32,
+        )
+        .to_image(),
+        None => original.clone(),
+    }
+}
+
+// Where the top left corner of the crop area is in the original screenshot
+pub fn offset(area: Option<Rectangle>) -> Point {
+    area.map_or(Point::default(), |area| Point::new(area.x, area.y))
+}
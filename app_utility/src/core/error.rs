@@ -0,0 +1,123 @@
+use super::{capture::Rectangle, export::ExportError, project::ProjectError};
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+// Everything that can go wrong while performing an action. Each variant carries what is
+// needed to tell the user (and the log) what failed
+#[derive(Debug)]
+pub enum AppError {
+    Capture {
+        screen: usize,
+        area: Option<Rectangle>,
+        message: String,
+    },
+    Encode {
+        path: PathBuf,
+        message: String,
+    },
+    Io {
+        path: PathBuf,
+        error: io::Error,
+    },
+    Open {
+        path: PathBuf,
+        message: String,
+    },
+    Clipboard(String),
+    Dialog(String),
+    NoScreenshot,
+    DuplicateShortcuts,
+}
+
+impl AppError {
+    // An error of `export::save` for the file at `path`
+    pub fn export(path: &Path, err: ExportError) -> Self {
+        match err {
+            ExportError::Io(error) => AppError::Io {
+                path: path.to_path_buf(),
+                error,
+            },
+            err => AppError::Encode {
+                path: path.to_path_buf(),
+                message: err.to_string(),
+            },
+        }
+    }
+
+    // An error of `project::save` for the file at `path`
+    pub fn save_project(path: &Path, err: ProjectError) -> Self {
+        match err {
+            ProjectError::Io(error) => AppError::Io {
+                path: path.to_path_buf(),
+                error,
+            },
+            err => AppError::Encode {
+                path: path.to_path_buf(),
+                message: err.to_string(),
+            },
+        }
+    }
+
+    // An error of `project::load` for the file at `path`
+    pub fn open_project(path: &Path, err: ProjectError) -> Self {
+        match err {
+            ProjectError::Io(error) => AppError::Io {
+                path: path.to_path_buf(),
+                error,
+            },
+            err => AppError::Open {
+                path: path.to_path_buf(),
+                message: err.to_string(),
+            },
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::Capture {
+                screen,
+                area: None,
+                message,
+            } => write!(f, "unable to capture screen {}: {}", screen, message),
+            AppError::Capture {
+                screen,
+                area: Some(area),
+                message,
+            } => write!(
+                f,
+                "unable to capture the area {}x{} at ({}, {}) of screen {}: {}",
+                area.width, area.height, area.x, area.y, screen, message
+            ),
+            AppError::Encode { path, message } => {
+                write!(f, "unable to save {}: {}", path.display(), message)
+            }
+            AppError::Io { path, error } => {
+                write!(f, "unable to access {}: {}", path.display(), error)
+            }
+            AppError::Open { path, message } => {
+                write!(f, "unable to open {}: {}", path.display(), message)
+            }
+            AppError::Clipboard(message) => {
+                write!(
+                    f,
+                    "unable to copy the screenshot to the clipboard: {}",
+                    message
+                )
+            }
+            AppError::Dialog(message) => write!(f, "unable to open the file dialog: {}", message),
+            AppError::NoScreenshot => write!(f, "there is no screenshot yet"),
+            AppError::DuplicateShortcuts => {
+                write!(
+                    f,
+                    "there are some actions with the same shortcut combination"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
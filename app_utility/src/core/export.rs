@@ -0,0 +1,232 @@
+use chrono::Local;
+use image::{
+    codecs::{
+        jpeg::JpegEncoder,
+        png::{CompressionType, FilterType, PngEncoder},
+        webp::{WebPEncoder, WebPQuality},
+    },
+    DynamicImage, ImageEncoder, ImageError, ImageOutputFormat, RgbaImage,
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt, fs, io, io::Cursor, path::Path};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ExportFormat {
+    Png,
+    Jpeg,
+    Gif,
+    WebP,
+    Bmp,
+    Tiff,
+    Qoi,
+}
+
+impl ExportFormat {
+    pub fn all() -> [ExportFormat; 7] {
+        [
+            ExportFormat::Png,
+            ExportFormat::Jpeg,
+            ExportFormat::Gif,
+            ExportFormat::WebP,
+            ExportFormat::Bmp,
+            ExportFormat::Tiff,
+            ExportFormat::Qoi,
+        ]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ExportFormat::Png => "PNG Image",
+            ExportFormat::Jpeg => "JPEG Image",
+            ExportFormat::Gif => "GIF Image",
+            ExportFormat::WebP => "WebP Image",
+            ExportFormat::Bmp => "BMP Image",
+            ExportFormat::Tiff => "TIFF Image",
+            ExportFormat::Qoi => "QOI Image",
+        }
+    }
+
+    pub fn extensions(self) -> &'static [&'static str] {
+        match self {
+            ExportFormat::Png => &["png"],
+            ExportFormat::Jpeg => &["jpg", "jpeg"],
+            ExportFormat::Gif => &["gif"],
+            ExportFormat::WebP => &["webp"],
+            ExportFormat::Bmp => &["bmp"],
+            ExportFormat::Tiff => &["tiff", "tif"],
+            ExportFormat::Qoi => &["qoi"],
+        }
+    }
+
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        let extension = extension.to_lowercase();
+        ExportFormat::all()
+            .into_iter()
+            .find(|format| format.extensions().contains(&extension.as_str()))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum PngCompression {
+    Fast,
+    Default,
+    Best,
+}
+
+impl PngCompression {
+    pub fn all() -> [PngCompression; 3] {
+        [
+            PngCompression::Fast,
+            PngCompression::Default,
+            PngCompression::Best,
+        ]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PngCompression::Fast => "Fast",
+            PngCompression::Default => "Default",
+            PngCompression::Best => "Best",
+        }
+    }
+
+    fn compression_type(self) -> CompressionType {
+        match self {
+            PngCompression::Fast => CompressionType::Fast,
+            PngCompression::Default => CompressionType::Default,
+            PngCompression::Best => CompressionType::Best,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ExportSettings {
+    pub jpeg_quality: u8,
+    pub png_compression: PngCompression,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        Self {
+            jpeg_quality: 90,
+            png_compression: PngCompression::Default,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ExportError {
+    UnknownExtension(String),
+    Encoding(ImageError),
+    Io(io::Error),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::UnknownExtension(extension) => write!(
+                f,
+                "unknown file extension \".{}\", supported extensions are: {}",
+                extension,
+                ExportFormat::all()
+                    .iter()
+                    .flat_map(|format| format.extensions())
+                    .copied()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            ExportError::Encoding(err) => write!(f, "unable to encode the image: {}", err),
+            ExportError::Io(err) => write!(f, "unable to write the file: {}", err),
+        }
+    }
+}
+
+impl From<ImageError> for ExportError {
+    fn from(err: ImageError) -> Self {
+        ExportError::Encoding(err)
+    }
+}
+
+impl From<io::Error> for ExportError {
+    fn from(err: io::Error) -> Self {
+        ExportError::Io(err)
+    }
+}
+
+pub fn encode(
+    image: &RgbaImage,
+    format: ExportFormat,
+    settings: &ExportSettings,
+) -> Result<Vec<u8>, ExportError> {
+    let mut bytes = Vec::new();
+    let mut writer = Cursor::new(&mut bytes);
+
+    match format {
+        ExportFormat::Png => {
+            PngEncoder::new_with_quality(
+                &mut writer,
+                settings.png_compression.compression_type(),
+                FilterType::Adaptive,
+            )
+            .write_image(
+                image.as_raw(),
+                image.width(),
+                image.height(),
+                image::ColorType::Rgba8,
+            )?;
+        }
+        ExportFormat::Jpeg => {
+            // JPEG has no alpha channel
+            let image = DynamicImage::ImageRgba8(image.clone()).to_rgb8();
+            JpegEncoder::new_with_quality(&mut writer, settings.jpeg_quality.clamp(1, 100))
+                .write_image(
+                    image.as_raw(),
+                    image.width(),
+                    image.height(),
+                    image::ColorType::Rgb8,
+                )?;
+        }
+        ExportFormat::WebP => {
+            WebPEncoder::new_with_quality(&mut writer, WebPQuality::lossless()).write_image(
+                image.as_raw(),
+                image.width(),
+                image.height(),
+                image::ColorType::Rgba8,
+            )?;
+        }
+        ExportFormat::Gif => write(image, &mut writer, ImageOutputFormat::Gif)?,
+        ExportFormat::Bmp => write(image, &mut writer, ImageOutputFormat::Bmp)?,
+        ExportFormat::Tiff => write(image, &mut writer, ImageOutputFormat::Tiff)?,
+        ExportFormat::Qoi => write(image, &mut writer, ImageOutputFormat::Qoi)?,
+    }
+    Ok(bytes)
+}
+
+fn write(
+    image: &RgbaImage,
+    writer: &mut Cursor<&mut Vec<u8>>,
+    format: ImageOutputFormat,
+) -> Result<(), ImageError> {
+    image.write_to(writer, format)
+}
+
+// Saves the screenshot choosing the format from the extension of the path
+pub fn save(image: &RgbaImage, path: &Path, settings: &ExportSettings) -> Result<(), ExportError> {
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let format =
+        ExportFormat::from_extension(&extension).ok_or(ExportError::UnknownExtension(extension))?;
+    fs::write(path, encode(image, format, settings)?)?;
+    Ok(())
+}
+
+pub fn build_default_name() -> String {
+    let now = Local::now()
+        .to_string()
+        .replace('-', "")
+        .replace(':', "_")
+        .replace(' ', "-");
+    format!("Screenshot_{}", now)[..28].to_string()
+}
@@ -0,0 +1,192 @@
+use super::{
+    annotation::{Annotation, Annotations, Color, Numbering, Point, Shape, ShapeId, Stroke},
+    capture::Rectangle,
+    crop,
+};
+use image::RgbaImage;
+
+// What the changes apply to: the annotations and the screenshot, which is the crop area
+// of the original one
+pub struct Document<'a> {
+    pub annotations: &'a mut Annotations,
+    pub image: &'a mut Option<RgbaImage>,
+    pub original: Option<&'a RgbaImage>,
+    pub crop: &'a mut Option<Rectangle>,
+}
+
+// A change of the screenshot. Applying a change returns the change that reverts it,
+// which is what the history keeps
+#[derive(Clone, Debug)]
+pub enum Change {
+    Add {
+        index: usize,
+        annotation: Annotation,
+    },
+    Delete(ShapeId),
+    Move {
+        id: ShapeId,
+        offset: Point,
+    },
+    Restyle {
+        id: ShapeId,
+        stroke: Stroke,
+    },
+    Refill {
+        id: ShapeId,
+        fill: Color,
+        rounding: f32,
+    },
+    // Replaces the geometry of the annotation, e.g. when it is resized
+    Reshape {
+        id: ShapeId,
+        shape: Shape,
+    },
+    // Keeps only this area of the original screenshot, or all of it
+    Crop(Option<Rectangle>),
+    // How the markers are labelled
+    Renumber(Numbering),
+}
+
+impl Change {
+    // Returns `None` if the change does not apply, e.g. the annotation does not exist anymore
+    fn apply(self, document: &mut Document) -> Option<Change> {
+        let annotations = &mut *document.annotations;
+        match self {
+            Change::Add { index, annotation } => {
+                let id = annotation.id;
+                annotations.insert(index, annotation);
+                Some(Change::Delete(id))
+            }
+            Change::Delete(id) => {
+                let (index, annotation) = annotations.remove(id)?;
+                Some(Change::Add { index, annotation })
+            }
+            Change::Move { id, offset } => {
+                annotations.get_mut(id)?.translate(offset);
+                Some(Change::Move {
+                    id,
+                    offset: -offset,
+                })
+            }
+            Change::Restyle { id, stroke } => {
+                let shape = annotations.get_mut(id)?;
+                let previous = shape.stroke();
+                shape.set_stroke(stroke);
+                Some(Change::Restyle {
+                    id,
+                    stroke: previous,
+                })
+            }
+            Change::Refill { id, fill, rounding } => {
+                let shape = annotations.get_mut(id)?;
+                let (previous, previous_rounding) = shape.fill()?;
+                shape.set_fill(fill, rounding);
+                Some(Change::Refill {
+                    id,
+                    fill: previous,
+                    rounding: previous_rounding,
+                })
+            }
+            Change::Reshape { id, shape } => {
+                let previous = std::mem::replace(annotations.get_mut(id)?, shape);
+                Some(Change::Reshape {
+                    id,
+                    shape: previous,
+                })
+            }
+            Change::Crop(area) => {
+                let original = document.original?;
+                let previous = *document.crop;
+                // The annotations stay on the same pixels of the original screenshot
+                annotations.translate(crop::offset(previous) - crop::offset(area));
+                *document.image = Some(crop::crop_image(original, area));
+                *document.crop = area;
+                Some(Change::Crop(previous))
+            }
+            Change::Renumber(numbering) => {
+                let previous = std::mem::replace(&mut annotations.numbering, numbering);
+                Some(Change::Renumber(previous))
+            }
+        }
+    }
+}
+
+// The changes that can be undone and redone, cleared when a new screenshot is taken
+#[derive(Default)]
+pub struct History {
+    undo: Vec<Change>,
+    redo: Vec<Change>,
+    // Whether the restyle, refill or renumbering on top of the undo stack is still being
+    // edited, until the edit ends or something is undone or redone
+    editing: bool,
+}
+
+impl History {
+    pub fn execute(&mut self, change: Change, document: &mut Document) {
+        // Consecutive restyles of the same annotation in one edit (e.g. while dragging the
+        // width) are undone at once, and so are the changes of the numbering
+        let merge = self.editing
+            && match (&change, self.undo.last()) {
+                (Change::Restyle { id, .. }, Some(Change::Restyle { id: last, .. }))
+                | (Change::Refill { id, .. }, Some(Change::Refill { id: last, .. })) => id == last,
+                (Change::Renumber(_), Some(Change::Renumber(_))) => true,
+                _ => false,
+            };
+        let editing = matches!(
+            change,
+            Change::Restyle { .. } | Change::Refill { .. } | Change::Renumber(_)
+        );
+        if let Some(inverse) = change.apply(document) {
+            if !merge {
+                self.undo.push(inverse);
+            }
+            self.redo.clear();
+            self.editing = editing;
+        }
+    }
+
+    // The next restyle, refill or renumbering is undone on its own, e.g. once the pointer is released
+    pub fn end_edit(&mut self) {
+        self.editing = false;
+    }
+
+    // Returns whether the screenshot itself changed, and not only its annotations
+    pub fn undo(&mut self, document: &mut Document) -> bool {
+        self.editing = false;
+        match self.undo.pop() {
+            Some(change) => {
+                let crop = matches!(change, Change::Crop(_));
+                self.redo.extend(change.apply(document));
+                crop
+            }
+            None => false,
+        }
+    }
+
+    // Returns whether the screenshot itself changed, and not only its annotations
+    pub fn redo(&mut self, document: &mut Document) -> bool {
+        self.editing = false;
+        match self.redo.pop() {
+            Some(change) => {
+                let crop = matches!(change, Change::Crop(_));
+                self.undo.extend(change.apply(document));
+                crop
+            }
+            None => false,
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.editing = false;
+    }
+}
@@ -0,0 +1,115 @@
+use super::{
+    capture::{CaptureBackend, Rectangle, ScreenInfo},
+    crop,
+};
+use image::{Rgba, RgbaImage};
+use std::path::PathBuf;
+
+// Serves fixed images instead of the content of the real screens, so that the application
+// can run and be tested without a display
+pub struct MockBackend {
+    screens: Vec<(ScreenInfo, RgbaImage)>,
+}
+
+impl Default for MockBackend {
+    // A single full HD screen
+    fn default() -> Self {
+        Self::synthetic(&[screen_info(0, 0, 0, 1920, 1080, 1.0)])
+    }
+}
+
+impl MockBackend {
+    pub fn new() -> Self {
+        Self {
+            screens: Vec::new(),
+        }
+    }
+
+    // Adds a screen whose content is `image`, which must have the physical size of the screen
+    pub fn with_screen(mut self, info: ScreenInfo, image: RgbaImage) -> Self {
+        self.screens.push((info, image));
+        self
+    }
+
+    // Screens filled with a gradient that is different for each screen
+    pub fn synthetic(screens: &[ScreenInfo]) -> Self {
+        let mut backend = Self::new();
+        for (i, info) in screens.iter().enumerate() {
+            let size = info.to_physical(info.bounds());
+            let image = synthetic_image(size.width as u32, size.height as u32, i as u8);
+            backend = backend.with_screen(*info, image);
+        }
+        backend
+    }
+
+    // One screen for each image file, placed side by side from left to right
+    pub fn from_files(paths: &[PathBuf]) -> Result<Self, String> {
+        let mut backend = Self::new();
+        let mut x = 0;
+        for (i, path) in paths.iter().enumerate() {
+            let image = image::open(path)
+                .map_err(|err| format!("unable to open {}: {}", path.display(), err))?
+                .to_rgba8();
+            let info = screen_info(i as u32, x, 0, image.width(), image.height(), 1.0);
+            x += image.width() as i32;
+            backend = backend.with_screen(info, image);
+        }
+        Ok(backend)
+    }
+
+    fn screen(&self, screen: usize) -> Result<&RgbaImage, String> {
+        self.screens
+            .get(screen)
+            .map(|(_, image)| image)
+            .ok_or_else(|| format!("there is no screen {}", screen))
+    }
+}
+
+impl CaptureBackend for MockBackend {
+    fn screens(&self) -> Result<Vec<ScreenInfo>, String> {
+        Ok(self.screens.iter().map(|(info, _)| *info).collect())
+    }
+
+    fn capture(&self, screen: usize) -> Result<RgbaImage, String> {
+        Ok(self.screen(screen)?.clone())
+    }
+
+    fn capture_area(&self, screen: usize, area: Rectangle) -> Result<RgbaImage, String> {
+        let image = self.screen(screen)?;
+        let area = crop::clamp(area, image.width(), image.height())
+            .ok_or_else(|| "the area is outside of the screen".to_string())?;
+        Ok(crop::crop_image(image, Some(area)))
+    }
+}
+
+pub fn screen_info(
+    id: u32,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    scale_factor: f32,
+) -> ScreenInfo {
+    ScreenInfo {
+        id,
+        x,
+        y,
+        width,
+        height,
+        scale_factor,
+        is_primary: id == 0,
+    }
+}
+
+// Deterministic content: red grows from left to right, green from top to bottom and blue
+// identifies the screen
+pub fn synthetic_image(width: u32, height: u32, seed: u8) -> RgbaImage {
+    RgbaImage::from_fn(width, height, |x, y| {
+        Rgba([
+            (x * 255 / width.max(1)) as u8,
+            (y * 255 / height.max(1)) as u8,
+            seed.wrapping_mul(64),
+            255,
+        ])
+    })
+}
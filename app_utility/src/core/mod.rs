@@ -0,0 +1,23 @@
+// Capture, annotation and export logic of the application, independent of the
+// window: the front ends dispatch `actions::Action`s to a `session::Session` and
+// apply the `session::Effect`s it returns
+pub mod actions;
+pub mod annotation;
+pub mod capture;
+pub mod config;
+pub mod crop;
+pub mod error;
+pub mod export;
+pub mod history;
+pub mod mock;
+pub mod path;
+pub mod project;
+pub mod rasterizer;
+pub mod scaling;
+pub mod selection;
+pub mod session;
+pub mod shortcut;
+pub mod svg;
+pub mod text;
+pub mod timer;
+pub mod zoom;
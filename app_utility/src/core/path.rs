@@ -0,0 +1,181 @@
+use super::annotation::{ArrowHead, Dash, Point, Shape};
+
+// Segments of the polyline that approximates a curved arrow
+const CURVE_SEGMENTS: usize = 32;
+// Angle between the sides of a head and the line
+const HEAD_ANGLE: f32 = std::f32::consts::TAU / 12.0;
+
+// What a line or an arrow is made of once its dashes and heads are laid out, so that the
+// viewer and the export draw the same thing
+pub struct LinePath {
+    // The whole line, stopped inside of the filled heads
+    pub points: Vec<Point>,
+    // Polylines drawn with the stroke and round ends, a single point is a dot
+    pub pieces: Vec<Vec<Point>>,
+    pub heads: Vec<Head>,
+}
+
+pub struct Head {
+    pub tip: Point,
+    // The other ends of the two sides
+    pub sides: [Point; 2],
+    // A filled head is a triangle, an open one is only its two sides
+    pub filled: bool,
+}
+
+// The points of the line from `start` to `end`, bent towards `control` if there is one
+pub fn curve(start: Point, control: Option<Point>, end: Point) -> Vec<Point> {
+    match control {
+        None => vec![start, end],
+        // Quadratic Bézier curve
+        Some(control) => (0..=CURVE_SEGMENTS)
+            .map(|i| {
+                let t = i as f32 / CURVE_SEGMENTS as f32;
+                let u = 1.0 - t;
+                start * (u * u) + control * (2.0 * u * t) + end * (t * t)
+            })
+            .collect(),
+    }
+}
+
+// `None` for the shapes that are not lines nor arrows
+pub fn line_path(shape: &Shape) -> Option<LinePath> {
+    let (mut points, width, dash, style) = match shape {
+        Shape::Line {
+            start,
+            end,
+            stroke,
+            dash,
+        } => (vec![*start, *end], stroke.width, *dash, None),
+        Shape::Arrow {
+            start,
+            end,
+            control,
+            stroke,
+            dash,
+            style,
+        } => (
+            curve(*start, *control, *end),
+            stroke.width,
+            *dash,
+            Some(*style),
+        ),
+        _ => return None,
+    };
+
+    let mut heads = Vec::new();
+    if let Some(style) = style.filter(|style| style.head != ArrowHead::None) {
+        let filled = style.head == ArrowHead::Filled;
+        let ends = if style.both_ends { 2 } else { 1 };
+        for _ in 0..ends {
+            // The head goes on the last point, in the direction the line has there
+            let tip = points[points.len() - 1];
+            let direction = match points.len() {
+                0 | 1 => Point::default(),
+                len => (tip - points[len - 2]).normalized(),
+            };
+            if direction.length() > 0.0 {
+                heads.push(Head {
+                    tip,
+                    sides: [
+                        tip - direction.rotated(HEAD_ANGLE) * style.size,
+                        tip - direction.rotated(-HEAD_ANGLE) * style.size,
+                    ],
+                    filled,
+                });
+                // The line stops inside of a filled head, so that it does not go past its tip
+                if filled {
+                    points = trimmed(&points, style.size * HEAD_ANGLE.cos() / 2.0);
+                }
+            }
+            points.reverse();
+        }
+        if ends == 1 {
+            points.reverse();
+        }
+    }
+
+    Some(LinePath {
+        pieces: dashed(&points, dash, width),
+        points,
+        heads,
+    })
+}
+
+// The polyline without the last `length` pixels of it
+fn trimmed(points: &[Point], mut length: f32) -> Vec<Point> {
+    let mut points = points.to_vec();
+    while points.len() >= 2 {
+        let last = points[points.len() - 1];
+        let previous = points[points.len() - 2];
+        let segment = last.distance(previous);
+        if segment > length {
+            let end = points.len() - 1;
+            points[end] = last + (previous - last).normalized() * length;
+            break;
+        }
+        length -= segment;
+        points.pop();
+    }
+    points
+}
+
+// Length of the dashes and of the gaps between them, `None` for a solid line. The dashes
+// have round ends, so the visible gaps are shorter than the pattern by the width of the
+// stroke and a dot is a dash of length 0
+pub fn dash_pattern(dash: Dash, width: f32) -> Option<(f32, f32)> {
+    match dash {
+        Dash::Solid => None,
+        Dash::Dashed => {
+            let on = (width * 3.0).max(6.0);
+            Some((on, on))
+        }
+        Dash::Dotted => Some((0.0, (width * 2.0).max(4.0))),
+    }
+}
+
+// Splits the polyline in the pieces that are drawn
+fn dashed(points: &[Point], dash: Dash, width: f32) -> Vec<Vec<Point>> {
+    let Some(first) = points.first() else {
+        return vec![];
+    };
+    let Some((on, off)) = dash_pattern(dash, width) else {
+        let mut points = points.to_vec();
+        points.dedup();
+        return vec![points];
+    };
+
+    let mut pieces = Vec::new();
+    let mut current = vec![*first];
+    let mut drawing = true;
+    // What is left of the current dash or gap
+    let mut left = on;
+    for pair in points.windows(2) {
+        let (mut a, b) = (pair[0], pair[1]);
+        let mut length = a.distance(b);
+        let direction = (b - a).normalized();
+        while length >= left {
+            a = a + direction * left;
+            length -= left;
+            if drawing {
+                current.push(a);
+                current.dedup();
+                pieces.push(std::mem::take(&mut current));
+                left = off;
+            } else {
+                current = vec![a];
+                left = on;
+            }
+            drawing = !drawing;
+        }
+        left -= length;
+        if drawing {
+            current.push(b);
+        }
+    }
+    current.dedup();
+    if drawing && current.len() >= 2 {
+        pieces.push(current);
+    }
+    pieces
+}
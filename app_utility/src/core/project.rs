@@ -0,0 +1,160 @@
+use super::{
+    annotation::{Numbering, Shape},
+    capture::Rectangle,
+};
+use image::{ImageError, ImageOutputFormat, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt, fs,
+    io::{self, Cursor, Read, Write},
+    path::Path,
+};
+use zip::{result::ZipError, write::FileOptions, ZipArchive, ZipWriter};
+
+// A project is a zip archive with the original screenshot and the annotations, so that
+// they can still be edited once it is opened again. The screenshot is stored with its raw
+// pixels, the redactions included are not applied to it
+pub const PROJECT_EXTENSION: &str = "shotproj";
+pub const PROJECT_NAME: &str = "Editable Project";
+
+// Bump when the layout of the document changes in a way older versions cannot read
+pub const PROJECT_VERSION: u32 = 1;
+
+const IMAGE_ENTRY: &str = "original.png";
+const DOCUMENT_ENTRY: &str = "annotations.json";
+
+// Everything needed to edit the screenshot again. The annotations are in the pixels of
+// the cropped screenshot, from the bottom to the top
+#[derive(Clone, PartialEq, Debug)]
+pub struct Project {
+    pub original: RgbaImage,
+    pub crop: Option<Rectangle>,
+    pub numbering: Numbering,
+    pub shapes: Vec<Shape>,
+}
+
+// What the JSON entry holds
+#[derive(Serialize, Deserialize)]
+struct Document {
+    version: u32,
+    crop: Option<Rectangle>,
+    numbering: Numbering,
+    shapes: Vec<Shape>,
+}
+
+#[derive(Debug)]
+pub enum ProjectError {
+    Io(io::Error),
+    Archive(ZipError),
+    Image(ImageError),
+    Document(serde_json::Error),
+    Version(u32),
+}
+
+impl fmt::Display for ProjectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProjectError::Io(err) => write!(f, "{}", err),
+            ProjectError::Archive(err) => write!(f, "invalid archive: {}", err),
+            ProjectError::Image(err) => write!(f, "invalid screenshot: {}", err),
+            ProjectError::Document(err) => write!(f, "invalid annotations: {}", err),
+            ProjectError::Version(version) => write!(
+                f,
+                "the project has version {}, but only version {} is supported",
+                version, PROJECT_VERSION
+            ),
+        }
+    }
+}
+
+impl From<io::Error> for ProjectError {
+    fn from(err: io::Error) -> Self {
+        ProjectError::Io(err)
+    }
+}
+
+impl From<ZipError> for ProjectError {
+    fn from(err: ZipError) -> Self {
+        match err {
+            ZipError::Io(err) => ProjectError::Io(err),
+            err => ProjectError::Archive(err),
+        }
+    }
+}
+
+impl From<ImageError> for ProjectError {
+    fn from(err: ImageError) -> Self {
+        ProjectError::Image(err)
+    }
+}
+
+impl From<serde_json::Error> for ProjectError {
+    fn from(err: serde_json::Error) -> Self {
+        ProjectError::Document(err)
+    }
+}
+
+pub fn is_project(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case(PROJECT_EXTENSION))
+}
+
+pub fn save(project: &Project, path: &Path) -> Result<(), ProjectError> {
+    let mut image = Vec::new();
+    project
+        .original
+        .write_to(&mut Cursor::new(&mut image), ImageOutputFormat::Png)?;
+    let document = serde_json::to_vec_pretty(&Document {
+        version: PROJECT_VERSION,
+        crop: project.crop,
+        numbering: project.numbering,
+        shapes: project.shapes.clone(),
+    })?;
+
+    let mut archive = ZipWriter::new(Cursor::new(Vec::new()));
+    // The PNG is already compressed
+    archive.start_file(
+        IMAGE_ENTRY,
+        FileOptions::default().compression_method(zip::CompressionMethod::Stored),
+    )?;
+    archive.write_all(&image)?;
+    archive.start_file(DOCUMENT_ENTRY, FileOptions::default())?;
+    archive.write_all(&document)?;
+    fs::write(path, archive.finish()?.into_inner())?;
+    Ok(())
+}
+
+pub fn load(path: &Path) -> Result<Project, ProjectError> {
+    let mut archive = ZipArchive::new(fs::File::open(path)?)?;
+
+    let mut document = Vec::new();
+    archive
+        .by_name(DOCUMENT_ENTRY)?
+        .read_to_end(&mut document)?;
+    // The version is checked first, a newer document may not parse at all
+    #[derive(Deserialize)]
+    struct Version {
+        version: u32,
+    }
+    let Version { version } = serde_json::from_slice(&document)?;
+    if version != PROJECT_VERSION {
+        return Err(ProjectError::Version(version));
+    }
+    let document: Document = serde_json::from_slice(&document)?;
+    if !document.numbering.is_valid() {
+        return Err(ProjectError::Document(serde::de::Error::custom(
+            "the letters of the markers start at A, not 0",
+        )));
+    }
+
+    let mut image = Vec::new();
+    archive.by_name(IMAGE_ENTRY)?.read_to_end(&mut image)?;
+    let original = image::load_from_memory(&image)?.to_rgba8();
+
+    Ok(Project {
+        original,
+        crop: document.crop,
+        numbering: document.numbering,
+        shapes: document.shapes,
+    })
+}
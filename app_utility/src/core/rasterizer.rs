@@ -0,0 +1,594 @@
+use super::{
+    annotation::{self, Annotations, Color, Point, Redaction, Shape, Stroke},
+    capture::Rectangle,
+    crop,
+    path::{self, LinePath},
+    text,
+};
+use image::{imageops, Rgba, RgbaImage};
+
+use ab_glyph::{Font, FontRef, PxScale, ScaleFont};
+
+// The proportional font of the editor, used for the labels of the markers, with the same
+// baseline tweak egui applies to it, so that the exported label lands where the preview
+// painted it
+pub const TEXT_FONT: &[u8] = include_bytes!("../../assets/fonts/Ubuntu-Light.ttf");
+pub const TEXT_BASELINE_OFFSET_FACTOR: f32 = -0.0333;
+
+// Coverage of a single shape over a region of the image. Every shape is first
+// rasterized here and then blended once, so that overlapping pieces of the same
+// stroke (e.g. the joints of a pen line) are not painted twice
+struct Coverage {
+    x0: i64,
+    y0: i64,
+    width: i64,
+    height: i64,
+    values: Vec<f32>,
+}
+
+impl Coverage {
+    // Covers the given bounds, clipped to the image
+    fn new(image: &RgbaImage, min: Point, max: Point) -> Self {
+        let x0 = (min.x.floor() as i64).max(0);
+        let y0 = (min.y.floor() as i64).max(0);
+        let x1 = (max.x.ceil() as i64).min(image.width() as i64);
+        let y1 = (max.y.ceil() as i64).min(image.height() as i64);
+        let width = (x1 - x0).max(0);
+        let height = (y1 - y0).max(0);
+        Self {
+            x0,
+            y0,
+            width,
+            height,
+            values: vec![0.0; (width * height) as usize],
+        }
+    }
+
+    fn set(&mut self, x: i64, y: i64, value: f32) {
+        let (x, y) = (x - self.x0, y - self.y0);
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return;
+        }
+        let cell = &mut self.values[(y * self.width + x) as usize];
+        *cell = cell.max(value.clamp(0.0, 1.0));
+    }
+
+    // Visits the pixels of the bounding box, passing the center of each pixel
+    fn for_each_in(&mut self, min: Point, max: Point, mut coverage_at: impl FnMut(Point) -> f32) {
+        let x_min = (min.x.floor() as i64).max(self.x0);
+        let y_min = (min.y.floor() as i64).max(self.y0);
+        let x_max = (max.x.ceil() as i64).min(self.x0 + self.width - 1);
+        let y_max = (max.y.ceil() as i64).min(self.y0 + self.height - 1);
+        for y in y_min..=y_max {
+            for x in x_min..=x_max {
+                let value = coverage_at(Point::new(x as f32 + 0.5, y as f32 + 0.5));
+                if value > 0.0 {
+                    self.set(x, y, value);
+                }
+            }
+        }
+    }
+
+    fn segment(&mut self, a: Point, b: Point, width: f32) {
+        let half = Point::new(width / 2.0 + 1.0, width / 2.0 + 1.0);
+        self.for_each_in(a.min(b) - half, a.max(b) + half, |p| {
+            edge_coverage(p.distance_to_segment(a, b), width)
+        });
+    }
+
+    fn polyline(&mut self, points: &[Point], width: f32) {
+        for pair in points.windows(2) {
+            self.segment(pair[0], pair[1], width);
+        }
+    }
+
+    fn disk(&mut self, center: Point, radius: f32) {
+        let extent = Point::new(radius + 1.0, radius + 1.0);
+        self.for_each_in(center - extent, center + extent, |p| {
+            (radius + 0.5 - p.distance(center)).clamp(0.0, 1.0)
+        });
+    }
+
+    // Antialiased convex polygon
+    fn convex_polygon(&mut self, points: &[Point]) {
+        let (min, max) = bounds(points);
+        let margin = Point::new(1.0, 1.0);
+        let area: f32 = points
+            .iter()
+            .zip(points.iter().cycle().skip(1))
+            .map(|(a, b)| a.x * b.y - b.x * a.y)
+            .sum();
+        let sign = if area >= 0.0 { 1.0 } else { -1.0 };
+        self.for_each_in(min - margin, max + margin, |p| {
+            // Distance to the farthest side the point is outside of, negative inside
+            let distance = points
+                .iter()
+                .zip(points.iter().cycle().skip(1))
+                .map(|(a, b)| {
+                    let edge = *b - *a;
+                    (p - *a).dot(Point::new(edge.y, -edge.x).normalized() * sign)
+                })
+                .fold(f32::NEG_INFINITY, f32::max);
+            (0.5 - distance).clamp(0.0, 1.0)
+        });
+    }
+
+    fn blend(&self, image: &mut RgbaImage, color: Color) {
+        let alpha = color.a as f32 / 255.0;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let value = self.values[(y * self.width + x) as usize];
+                if value > 0.0 {
+                    let pixel = image.get_pixel_mut((x + self.x0) as u32, (y + self.y0) as u32);
+                    blend_pixel(pixel, [color.r, color.g, color.b], alpha * value);
+                }
+            }
+        }
+    }
+
+    // Like a highlighter on paper: each channel is multiplied by the one of the color, so
+    // what is under the stroke stays readable
+    fn multiply(&self, image: &mut RgbaImage, color: Color) {
+        let alpha = color.a as f32 / 255.0;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let value = self.values[(y * self.width + x) as usize] * alpha;
+                if value > 0.0 {
+                    let pixel = image.get_pixel_mut((x + self.x0) as u32, (y + self.y0) as u32);
+                    for (i, channel) in [color.r, color.g, color.b].iter().enumerate() {
+                        let factor = 1.0 - value * (1.0 - *channel as f32 / 255.0);
+                        pixel[i] = (pixel[i] as f32 * factor).round() as u8;
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Antialiased coverage of a pixel whose center is `distance` away from the middle of a stroke
+fn edge_coverage(distance: f32, width: f32) -> f32 {
+    (width / 2.0 + 0.5 - distance).clamp(0.0, 1.0)
+}
+
+fn blend_pixel(pixel: &mut Rgba<u8>, color: [u8; 3], alpha: f32) {
+    let dst_alpha = pixel[3] as f32 / 255.0;
+    let out_alpha = alpha + dst_alpha * (1.0 - alpha);
+    if out_alpha <= 0.0 {
+        return;
+    }
+    for (i, channel) in color.iter().enumerate() {
+        let src = *channel as f32 * alpha;
+        let dst = pixel[i] as f32 * dst_alpha * (1.0 - alpha);
+        pixel[i] = ((src + dst) / out_alpha).round() as u8;
+    }
+    pixel[3] = (out_alpha * 255.0).round() as u8;
+}
+
+fn bounds(points: &[Point]) -> (Point, Point) {
+    points.iter().fold(
+        (
+            Point::new(f32::INFINITY, f32::INFINITY),
+            Point::new(f32::NEG_INFINITY, f32::NEG_INFINITY),
+        ),
+        |(min, max), p| (min.min(*p), max.max(*p)),
+    )
+}
+
+fn draw(
+    image: &mut RgbaImage,
+    color: Color,
+    width: f32,
+    points: &[Point],
+    shape: impl FnOnce(&mut Coverage),
+) {
+    if color.a == 0 || points.is_empty() {
+        return;
+    }
+    let margin = Point::new(width / 2.0 + 1.0, width / 2.0 + 1.0);
+    let (min, max) = bounds(points);
+    let mut coverage = Coverage::new(image, min - margin, max + margin);
+    shape(&mut coverage);
+    coverage.blend(image, color);
+}
+
+// Fills a closed shape and then strokes its border. `distance` is the signed distance from
+// a point to the border, negative inside of the shape, which fits between `min` and `max`
+fn draw_outline(
+    image: &mut RgbaImage,
+    min: Point,
+    max: Point,
+    stroke: Stroke,
+    fill: Color,
+    distance: impl Fn(Point) -> f32,
+) {
+    let margin = Point::new(1.0, 1.0);
+    draw(image, fill, 0.0, &[min, max], |c| {
+        c.for_each_in(min - margin, max + margin, |p| {
+            (0.5 - distance(p)).clamp(0.0, 1.0)
+        })
+    });
+    let margin = Point::new(stroke.width / 2.0 + 1.0, stroke.width / 2.0 + 1.0);
+    draw(image, stroke.color, stroke.width, &[min, max], |c| {
+        c.for_each_in(min - margin, max + margin, |p| {
+            edge_coverage(distance(p).abs(), stroke.width)
+        })
+    });
+}
+
+// The dashes, the dots and the heads of a line or an arrow, blended at once
+fn draw_line_path(image: &mut RgbaImage, line_path: &LinePath, stroke: Stroke) {
+    let points: Vec<Point> = line_path
+        .pieces
+        .iter()
+        .flatten()
+        .chain(
+            line_path
+                .heads
+                .iter()
+                .flat_map(|head| [&head.tip, &head.sides[0], &head.sides[1]]),
+        )
+        .copied()
+        .collect();
+    draw(image, stroke.color, stroke.width, &points, |c| {
+        for piece in &line_path.pieces {
+            match piece.as_slice() {
+                [dot] => c.disk(*dot, stroke.width / 2.0),
+                piece => c.polyline(piece, stroke.width),
+            }
+        }
+        for head in &line_path.heads {
+            let [left, right] = head.sides;
+            if head.filled {
+                c.convex_polygon(&[head.tip, left, right]);
+            } else {
+                c.polyline(&[left, head.tip, right], stroke.width);
+            }
+        }
+    });
+}
+
+fn highlight(image: &mut RgbaImage, points: &[Point], stroke: Stroke) {
+    if points.len() < 2 || stroke.color.a == 0 {
+        return;
+    }
+    let margin = Point::new(stroke.width / 2.0 + 1.0, stroke.width / 2.0 + 1.0);
+    let (min, max) = bounds(points);
+    let mut coverage = Coverage::new(image, min - margin, max + margin);
+    coverage.polyline(points, stroke.width);
+    coverage.multiply(image, stroke.color);
+}
+
+// Width and height of the text as it is rendered with the font of the editor, in pixels
+pub fn text_size(text: &str, size: f32) -> Point {
+    let font = FontRef::try_from_slice(TEXT_FONT).unwrap();
+    let width = text.lines().fold(0.0f32, |width, line| {
+        width.max(text::line_width(&font, size, line))
+    });
+    Point::new(
+        width,
+        text::line_height(&font, size) * text.lines().count().max(1) as f32,
+    )
+}
+
+// `baseline_offset` moves the text down, in pixels
+fn draw_text(
+    image: &mut RgbaImage,
+    font: &FontRef,
+    top_left: Point,
+    text: &str,
+    size: f32,
+    color: Color,
+    baseline_offset: f32,
+) {
+    let scaled = font.as_scaled(PxScale::from(size));
+    let line_height = scaled.ascent() - scaled.descent() + scaled.line_gap();
+    let mut baseline = top_left.y + scaled.ascent() + baseline_offset;
+
+    let mut outlines = Vec::new();
+    for line in text.lines() {
+        let mut caret = top_left.x;
+        let mut previous = None;
+        for character in line.chars() {
+            let glyph_id = font.glyph_id(character);
+            if let Some(previous) = previous {
+                caret += scaled.kern(previous, glyph_id);
+            }
+            let glyph = glyph_id.with_scale_and_position(size, ab_glyph::point(caret, baseline));
+            caret += scaled.h_advance(glyph_id);
+            previous = Some(glyph_id);
+
+            outlines.extend(font.outline_glyph(glyph));
+        }
+        baseline += line_height;
+    }
+
+    let corners: Vec<Point> = outlines
+        .iter()
+        .flat_map(|outlined| {
+            let px = outlined.px_bounds();
+            [
+                Point::new(px.min.x, px.min.y),
+                Point::new(px.max.x, px.max.y),
+            ]
+        })
+        .collect();
+    draw(image, color, 0.0, &corners, |coverage| {
+        for outlined in outlines {
+            let px = outlined.px_bounds();
+            outlined.draw(|x, y, value| {
+                coverage.set(
+                    px.min.x as i64 + x as i64,
+                    px.min.y as i64 + y as i64,
+                    value,
+                );
+            });
+        }
+    });
+}
+
+// The pixels of the area between `start` and `end` as the redaction renders them, with the
+// position of their top left corner in the image. `None` if the area is outside of it
+fn redacted(
+    image: &RgbaImage,
+    start: Point,
+    end: Point,
+    redaction: Redaction,
+) -> Option<(u32, u32, RgbaImage)> {
+    let (min, max) = (start.min(end), start.max(end));
+    let area = crop::clamp(
+        Rectangle {
+            x: min.x,
+            y: min.y,
+            width: max.x - min.x,
+            height: max.y - min.y,
+        },
+        image.width(),
+        image.height(),
+    )?;
+    let (x, y) = (area.x as u32, area.y as u32);
+    let (width, height) = (area.width as u32, area.height as u32);
+    let pixels = match redaction {
+        Redaction::Blur(sigma) => {
+            // The pixels around the area are blurred too, so that its edges are as blurred
+            // as its middle
+            let margin = (sigma * 3.0).ceil();
+            let around = crop::clamp(
+                Rectangle {
+                    x: area.x - margin,
+                    y: area.y - margin,
+                    width: area.width + 2.0 * margin,
+                    height: area.height + 2.0 * margin,
+                },
+                image.width(),
+                image.height(),
+            )?;
+            let (around_x, around_y) = (around.x as u32, around.y as u32);
+            let blurred = imageops::blur(
+                &imageops::crop_imm(
+                    image,
+                    around_x,
+                    around_y,
+                    around.width as u32,
+                    around.height as u32,
+                )
+                .to_image(),
+                sigma.max(0.1),
+            );
+            imageops::crop_imm(&blurred, x - around_x, y - around_y, width, height).to_image()
+        }
+        Redaction::Pixelate(block_size) => {
+            let mut pixels = imageops::crop_imm(image, x, y, width, height).to_image();
+            let block_size = block_size.max(1);
+            for block_y in (0..height).step_by(block_size as usize) {
+                for block_x in (0..width).step_by(block_size as usize) {
+                    let block_width = block_size.min(width - block_x);
+                    let block_height = block_size.min(height - block_y);
+                    let mut sum = [0u64; 4];
+                    for py in block_y..block_y + block_height {
+                        for px in block_x..block_x + block_width {
+                            for (total, channel) in sum.iter_mut().zip(pixels.get_pixel(px, py).0) {
+                                *total += channel as u64;
+                            }
+                        }
+                    }
+                    let count = (block_width * block_height) as u64;
+                    let average = Rgba(sum.map(|total| (total / count) as u8));
+                    for py in block_y..block_y + block_height {
+                        for px in block_x..block_x + block_width {
+                            pixels.put_pixel(px, py, average);
+                        }
+                    }
+                }
+            }
+            pixels
+        }
+        // Opaque whatever the alpha of the color, so that nothing shows through
+        Redaction::Fill(color) => {
+            RgbaImage::from_pixel(width, height, Rgba([color.r, color.g, color.b, 255]))
+        }
+    };
+    Some((x, y, pixels))
+}
+
+// Where the label of a marker is written, with its size and color: centered on the
+// badge, in black or white depending on which one stands out on the badge
+pub fn marker_label(center: Point, radius: f32, label: &str, color: Color) -> (Point, f32, Color) {
+    let size = radius * if label.chars().count() > 2 { 0.9 } else { 1.2 };
+    let top_left = center - text_size(label, size) * 0.5;
+    let luminance = 0.299 * color.r as f32 + 0.587 * color.g as f32 + 0.114 * color.b as f32;
+    let text_color = if luminance > 150.0 {
+        Color::BLACK
+    } else {
+        Color::rgb(255, 255, 255)
+    };
+    (top_left, size, text_color)
+}
+
+// The pixels around a highlight stroke with the stroke on them, with the position of
+// their top left corner in the image
+fn highlighted(
+    image: &RgbaImage,
+    points: &[Point],
+    stroke: Stroke,
+) -> Option<(u32, u32, RgbaImage)> {
+    let margin = Point::new(stroke.width / 2.0 + 1.0, stroke.width / 2.0 + 1.0);
+    let (min, max) = bounds(points);
+    let area = Coverage::new(image, min - margin, max + margin);
+    if area.width == 0 || area.height == 0 {
+        return None;
+    }
+    let (x, y) = (area.x0 as u32, area.y0 as u32);
+    let mut pixels =
+        imageops::crop_imm(image, x, y, area.width as u32, area.height as u32).to_image();
+    let offset = Point::new(x as f32, y as f32);
+    let points: Vec<Point> = points.iter().map(|p| *p - offset).collect();
+    highlight(&mut pixels, &points, stroke);
+    Some((x, y, pixels))
+}
+
+// The pixels of the image under the shape as they are exported, with the position of their
+// top left corner, for the shapes that a painter that only blends colors cannot render:
+// the redactions and the highlights
+pub fn rendered(image: &RgbaImage, shape: &Shape) -> Option<(u32, u32, RgbaImage)> {
+    match shape {
+        Shape::Redact {
+            start,
+            end,
+            redaction,
+        } => redacted(image, *start, *end, *redaction),
+        Shape::Highlight { points, stroke } => highlighted(image, points, *stroke),
+        _ => None,
+    }
+}
+
+// `rendered` for a shape over the annotations `below` it, which are burned in the pixels
+// of the image first like in the export
+pub fn rendered_over(
+    image: &RgbaImage,
+    below: &Annotations,
+    shape: &Shape,
+) -> Option<(u32, u32, RgbaImage)> {
+    if below.is_empty() || !matches!(shape, Shape::Redact { .. } | Shape::Highlight { .. }) {
+        return rendered(image, shape);
+    }
+    let mut image = image.clone();
+    burn_annotations(&mut image, below);
+    rendered(&image, shape)
+}
+
+// Renders the annotations on the full resolution screenshot
+pub fn burn_annotations(image: &mut RgbaImage, annotations: &Annotations) {
+    let font = FontRef::try_from_slice(TEXT_FONT).unwrap();
+
+    let mut markers = 0;
+    for shape in annotations.shapes() {
+        match shape {
+            Shape::Pen { points, stroke } => {
+                if points.len() >= 2 {
+                    draw(image, stroke.color, stroke.width, points, |c| {
+                        c.polyline(points, stroke.width)
+                    });
+                }
+            }
+            // The stroke is blended once, so it does not get darker where it overlaps itself
+            Shape::Highlight { points, stroke } => highlight(image, points, *stroke),
+            Shape::Line { stroke, .. } | Shape::Arrow { stroke, .. } => {
+                if let Some(line_path) = path::line_path(shape) {
+                    draw_line_path(image, &line_path, *stroke);
+                }
+            }
+            Shape::Rect {
+                start,
+                end,
+                stroke,
+                fill,
+                rounding,
+            } => {
+                let (min, max) = (start.min(*end), start.max(*end));
+                draw_outline(image, min, max, *stroke, *fill, |p| {
+                    annotation::rounded_rect_distance(p, min, max, *rounding)
+                });
+            }
+            Shape::Circle {
+                center,
+                radius,
+                stroke,
+                fill,
+            } => {
+                let extent = Point::new(*radius, *radius);
+                draw_outline(
+                    image,
+                    *center - extent,
+                    *center + extent,
+                    *stroke,
+                    *fill,
+                    |p| p.distance(*center) - radius,
+                );
+            }
+            Shape::Ellipse {
+                start,
+                end,
+                stroke,
+                fill,
+            } => {
+                let (min, max) = (start.min(*end), start.max(*end));
+                draw_outline(image, min, max, *stroke, *fill, |p| {
+                    annotation::ellipse_distance(p, min, max)
+                });
+            }
+            Shape::Text { pos, text, style } => {
+                let layout = text::layout(text, style);
+                let (min, max) = (*pos, *pos + layout.size);
+                draw_outline(
+                    image,
+                    min,
+                    max,
+                    Stroke::new(0.0, Color::TRANSPARENT),
+                    style.background,
+                    |p| annotation::rounded_rect_distance(p, min, max, 0.0),
+                );
+                let font = style.font();
+                for (offset, line) in layout.lines {
+                    draw_text(
+                        image,
+                        &font,
+                        *pos + offset,
+                        &line,
+                        style.size,
+                        style.color,
+                        0.0,
+                    );
+                }
+            }
+            Shape::Marker {
+                center,
+                radius,
+                color,
+            } => {
+                let extent = Point::new(*radius, *radius);
+                draw(
+                    image,
+                    *color,
+                    0.0,
+                    &[*center - extent, *center + extent],
+                    |c| c.disk(*center, *radius),
+                );
+                let label = annotations.numbering.label(markers);
+                let (top_left, size, text_color) = marker_label(*center, *radius, &label, *color);
+                let offset = size * TEXT_BASELINE_OFFSET_FACTOR;
+                draw_text(image, &font, top_left, &label, size, text_color, offset);
+                markers += 1;
+            }
+            // The pixels under the area are replaced, so they cannot be recovered
+            Shape::Redact {
+                start,
+                end,
+                redaction,
+            } => {
+                if let Some((x, y, pixels)) = redacted(image, *start, *end, *redaction) {
+                    imageops::replace(image, &pixels, x as i64, y as i64);
+                }
+            }
+        }
+    }
+}
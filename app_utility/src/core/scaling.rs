@@ -0,0 +1,35 @@
+use super::{annotation::Point, capture::Rectangle};
+
+// Conversions between points, in which the window, the desktop and the selected areas are
+// measured, and the physical pixels of the screens and of the screenshots. A point is
+// `scale` pixels, the scale factor the system reports for the screen, on every platform
+
+pub fn to_pixels(points: f32, scale: f32) -> f32 {
+    points * scale
+}
+
+pub fn to_points(pixels: f32, scale: f32) -> f32 {
+    pixels / scale
+}
+
+// An area in points, relative to `origin`, in the whole pixels it covers
+pub fn area_to_pixels(area: Rectangle, origin: Point, scale: f32) -> Rectangle {
+    snap(Rectangle {
+        x: to_pixels(area.x - origin.x, scale),
+        y: to_pixels(area.y - origin.y, scale),
+        width: to_pixels(area.width, scale),
+        height: to_pixels(area.height, scale),
+    })
+}
+
+// The area with its edges rounded to whole pixels. The edges are rounded rather than the
+// size, so that areas side by side stay side by side, without a gap or an overlap
+pub fn snap(area: Rectangle) -> Rectangle {
+    let (x0, y0) = (area.x.round(), area.y.round());
+    Rectangle {
+        x: x0,
+        y: y0,
+        width: (area.x + area.width).round() - x0,
+        height: (area.y + area.height).round() - y0,
+    }
+}
@@ -0,0 +1,172 @@
+use super::{
+    annotation::{self, Annotations, Point, Shape, ShapeId},
+    path, text,
+};
+
+fn points_bounds(points: &[Point]) -> (Point, Point) {
+    points.iter().fold(
+        (
+            Point::new(f32::INFINITY, f32::INFINITY),
+            Point::new(f32::NEG_INFINITY, f32::NEG_INFINITY),
+        ),
+        |(min, max), p| (min.min(*p), max.max(*p)),
+    )
+}
+
+// Top left and bottom right corners of the box containing the shape, stroke excluded
+pub fn bounds(shape: &Shape) -> (Point, Point) {
+    match shape {
+        Shape::Pen { points, .. } | Shape::Highlight { points, .. } => points_bounds(points),
+        Shape::Arrow {
+            start,
+            end,
+            control,
+            ..
+        } => points_bounds(&path::curve(*start, *control, *end)),
+        Shape::Line { start, end, .. }
+        | Shape::Rect { start, end, .. }
+        | Shape::Ellipse { start, end, .. }
+        | Shape::Redact { start, end, .. } => (start.min(*end), start.max(*end)),
+        Shape::Circle { center, radius, .. } | Shape::Marker { center, radius, .. } => {
+            let extent = Point::new(*radius, *radius);
+            (*center - extent, *center + extent)
+        }
+        Shape::Text { pos, text, style } => (*pos, *pos + text::layout(text, style).size),
+    }
+}
+
+// The annotation under `point`, the topmost one if they overlap. `tolerance` is how far
+// from a stroke the point can be, in pixels of the screenshot
+pub fn hit_test(annotations: &Annotations, point: Point, tolerance: f32) -> Option<ShapeId> {
+    annotations
+        .items()
+        .iter()
+        .rev()
+        .find(|annotation| hits(&annotation.shape, point, tolerance))
+        .map(|annotation| annotation.id)
+}
+
+fn hits(shape: &Shape, point: Point, tolerance: f32) -> bool {
+    let near = |distance: f32| distance <= shape.stroke().width / 2.0 + tolerance;
+    // A filled shape is hit anywhere inside of it, otherwise only on its border
+    let filled = shape.fill().is_some_and(|(fill, _)| fill.a > 0);
+    let hits_border = |distance: f32| near(distance.abs()) || (filled && distance <= 0.0);
+    match shape {
+        Shape::Pen { points, .. } | Shape::Highlight { points, .. } => points
+            .windows(2)
+            .any(|pair| near(point.distance_to_segment(pair[0], pair[1]))),
+        Shape::Line { start, end, .. } => near(point.distance_to_segment(*start, *end)),
+        Shape::Arrow {
+            start,
+            end,
+            control,
+            ..
+        } => path::curve(*start, *control, *end)
+            .windows(2)
+            .any(|pair| near(point.distance_to_segment(pair[0], pair[1]))),
+        Shape::Rect { rounding, .. } => {
+            let (min, max) = bounds(shape);
+            hits_border(annotation::rounded_rect_distance(
+                point, min, max, *rounding,
+            ))
+        }
+        Shape::Ellipse { .. } => {
+            let (min, max) = bounds(shape);
+            hits_border(annotation::ellipse_distance(point, min, max))
+        }
+        Shape::Circle { center, radius, .. } => hits_border(point.distance(*center) - radius),
+        Shape::Marker { center, radius, .. } => point.distance(*center) <= radius + tolerance,
+        // The whole area is covered, not only its border
+        Shape::Text { .. } | Shape::Redact { .. } => {
+            let (min, max) = bounds(shape);
+            let margin = Point::new(tolerance, tolerance);
+            let (min, max) = (min - margin, max + margin);
+            point.x >= min.x && point.x <= max.x && point.y >= min.y && point.y <= max.y
+        }
+    }
+}
+
+fn corners(min: Point, max: Point) -> [Point; 4] {
+    [min, Point::new(max.x, min.y), max, Point::new(min.x, max.y)]
+}
+
+// Points that can be dragged to resize the shape
+pub fn handles(shape: &Shape) -> Vec<Point> {
+    match shape {
+        Shape::Pen { .. }
+        | Shape::Highlight { .. }
+        | Shape::Rect { .. }
+        | Shape::Ellipse { .. }
+        | Shape::Redact { .. } => {
+            let (min, max) = bounds(shape);
+            corners(min, max).to_vec()
+        }
+        Shape::Line { start, end, .. } => vec![*start, *end],
+        // The control point of a curved arrow bends it
+        Shape::Arrow {
+            start,
+            end,
+            control,
+            ..
+        } => [*start, *end].into_iter().chain(*control).collect(),
+        Shape::Circle { center, radius, .. } | Shape::Marker { center, radius, .. } => {
+            vec![*center + Point::new(*radius, 0.0)]
+        }
+        Shape::Text { .. } => vec![],
+    }
+}
+
+pub fn handle_at(shape: &Shape, point: Point, tolerance: f32) -> Option<usize> {
+    handles(shape)
+        .iter()
+        .position(|handle| handle.distance(point) <= tolerance)
+}
+
+// The shape after dragging one of its handles to `to`
+pub fn resized(shape: &Shape, handle: usize, to: Point) -> Shape {
+    let (min, max) = bounds(shape);
+    let mut shape = shape.clone();
+    match &mut shape {
+        Shape::Pen { points, .. } | Shape::Highlight { points, .. } => {
+            // The opposite corner stays where it is and the points are stretched
+            let corners = corners(min, max);
+            let fixed = corners[(handle + 2) % 4];
+            let moved = corners[handle];
+            let scale = |from: f32, to: f32, origin: f32| {
+                if from == origin {
+                    1.0
+                } else {
+                    (to - origin) / (from - origin)
+                }
+            };
+            let scale_x = scale(moved.x, to.x, fixed.x);
+            let scale_y = scale(moved.y, to.y, fixed.y);
+            for point in points.iter_mut() {
+                *point = Point::new(
+                    fixed.x + (point.x - fixed.x) * scale_x,
+                    fixed.y + (point.y - fixed.y) * scale_y,
+                );
+            }
+        }
+        Shape::Line { start, end, .. } | Shape::Arrow { start, end, .. } if handle < 2 => {
+            if handle == 0 {
+                *start = to;
+            } else {
+                *end = to;
+            }
+        }
+        Shape::Arrow { control, .. } => *control = Some(to),
+        Shape::Line { .. } => {}
+        Shape::Rect { start, end, .. }
+        | Shape::Ellipse { start, end, .. }
+        | Shape::Redact { start, end, .. } => {
+            *start = corners(min, max)[(handle + 2) % 4];
+            *end = to;
+        }
+        Shape::Circle { center, radius, .. } | Shape::Marker { center, radius, .. } => {
+            *radius = center.distance(to)
+        }
+        Shape::Text { .. } => {}
+    }
+    shape
+}
@@ -0,0 +1,556 @@
+use super::{
+    actions::Action,
+    annotation::{
+        Annotations, ArrowStyle, Color, Dash, Numbering, Point, Redaction, Shape, ShapeId, Stroke,
+    },
+    capture::{FrozenDesktop, Rectangle, Screenshots},
+    config::{Config, CONFIG_VERSION},
+    crop,
+    error::AppError,
+    export::{self, build_default_name, ExportSettings},
+    history::{Change, Document, History},
+    project::{self, Project},
+    rasterizer,
+    shortcut::AllShortcuts,
+    svg,
+    timer::Timer,
+    zoom::Zoom,
+};
+use image::RgbaImage;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Selection {
+    Fullscreen,
+    Area,
+    // Every screen at once, whichever is selected in the settings
+    AllScreens,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Modifier {
+    NotSelected,
+    Pen,
+    Rect,
+    Arrow,
+    Text,
+    Crop,
+    Line,
+    Circle,
+    Ellipse,
+    Select,
+    Redact,
+    Highlight,
+    Marker,
+    CurvedArrow,
+}
+
+// What the front end has to do after the state changed
+#[derive(Clone, PartialEq, Debug)]
+pub enum Effect {
+    // Hide the window, so that it is not in the screenshot, and then call `Session::capture`
+    HideWindow,
+    // The screens were captured for an area to be selected on them, in `Session::frozen`
+    DesktopFrozen,
+    ShowWindow,
+    Close,
+    CopyToClipboard,
+    // Ask where to save the screenshot and then call `Session::save_to`
+    ChooseSavePath { location: PathBuf, filename: String },
+    // Ask for an image or a project and then call `Session::open`
+    ChooseOpenPath { location: PathBuf },
+    ImageChanged,
+    Repaint,
+}
+
+pub struct Session {
+    // The screenshot as it is edited: the crop area of the original one
+    pub image: Option<RgbaImage>,
+    pub original: Option<RgbaImage>,
+    pub crop: Option<Rectangle>,
+    pub annotations: Annotations,
+    // The area to capture, on the desktop in points
+    pub area: Rectangle,
+    // The screens as they were when the area shot was taken, the area is cut from them
+    pub frozen: Option<FrozenDesktop>,
+    pub default_name: String,
+    pub default_name_selected: bool,
+    pub default_number: usize,
+    pub default_path: String,
+    pub export_settings: ExportSettings,
+    pub hide: bool,
+    pub history: History,
+    pub modification: bool,
+    pub modifier: Modifier,
+    pub screenshots: Screenshots,
+    pub selecting_area: bool,
+    pub selection_mode: Selection,
+    pub shortcuts: AllShortcuts,
+    pub show_settings: bool,
+    pub timer: Timer,
+    pub view_image: bool,
+    pub zoom: Zoom,
+}
+
+impl Session {
+    pub fn new(screenshots: Screenshots) -> Self {
+        Self {
+            image: None,
+            original: None,
+            crop: None,
+            annotations: Annotations::default(),
+            area: Rectangle::default(),
+            frozen: None,
+            default_name: build_default_name(),
+            default_name_selected: true,
+            default_number: 0,
+            default_path: "screenshots".to_string(),
+            export_settings: ExportSettings::default(),
+            hide: false,
+            history: History::default(),
+            modification: false,
+            modifier: Modifier::NotSelected,
+            screenshots,
+            selecting_area: false,
+            selection_mode: Selection::Fullscreen,
+            shortcuts: AllShortcuts::default(),
+            show_settings: false,
+            timer: Timer::new(),
+            view_image: false,
+            zoom: Zoom::Fit,
+        }
+    }
+
+    pub fn apply_config(&mut self, config: Config) {
+        self.default_path = config.default_path;
+        if let Some(name) = config.default_name {
+            self.default_name = name;
+            self.default_name_selected = false;
+        }
+        if config.screen_number < self.screenshots.total_screens() {
+            self.screenshots.screen_number = config.screen_number;
+        }
+        self.screenshots.all_screens = config.all_screens;
+        self.export_settings = config.export;
+        self.shortcuts = AllShortcuts::from_saved(config.shortcuts);
+    }
+
+    pub fn config(&self) -> Config {
+        Config {
+            version: CONFIG_VERSION,
+            default_path: self.default_path.clone(),
+            default_name: if self.default_name_selected {
+                None
+            } else {
+                Some(self.default_name.clone())
+            },
+            screen_number: self.screenshots.screen_number,
+            all_screens: self.screenshots.all_screens,
+            export: self.export_settings,
+            shortcuts: self.shortcuts.vec.clone(),
+        }
+    }
+
+    pub fn dispatch(&mut self, action: Action) -> Result<Vec<Effect>, AppError> {
+        let effects = match action {
+            // The area is cut from the frozen screens, they are not captured again
+            Action::Capture if self.frozen.is_some() => self.capture_area()?,
+            Action::Capture => {
+                self.hide = true;
+                self.timer.reset_timer();
+                vec![Effect::HideWindow]
+            }
+            Action::Close => vec![Effect::Close],
+            Action::Copy => vec![Effect::CopyToClipboard],
+            Action::HomePage => {
+                self.selecting_area = false;
+                self.frozen = None;
+                self.view_image = false;
+                self.show_settings = false;
+                vec![]
+            }
+            Action::ManageTimer => {
+                // Nothing to count down before the timer is started
+                let Some(start) = self.timer.start_instant else {
+                    return Ok(vec![]);
+                };
+                let mut effects = vec![];
+                let now = Instant::now();
+                if now.duration_since(start).as_secs_f32() >= 1.0 {
+                    self.timer.decrement_timer();
+                    if self.timer.seconds == 0 {
+                        effects = self.dispatch(Action::Capture)?;
+                    }
+                    self.timer.start_instant = Some(now);
+                }
+                effects.push(Effect::Repaint);
+                effects
+            }
+            Action::Modify => {
+                self.modification = true;
+                vec![]
+            }
+            Action::NewScreenshot => {
+                self.hide = false;
+                self.view_image = false;
+                self.selection_mode = Selection::Fullscreen;
+                self.selecting_area = false;
+                self.frozen = None;
+                self.show_settings = false;
+                self.annotations.clear();
+                self.history.clear();
+                self.modifier = Modifier::NotSelected;
+                self.modification = false;
+                vec![]
+            }
+            Action::ResetTimer => {
+                self.timer.reset_timer();
+                vec![]
+            }
+            Action::Open => vec![Effect::ChooseOpenPath {
+                location: self.default_location()?,
+            }],
+            Action::Save => {
+                let mut filename = build_default_name();
+                if !self.default_name_selected {
+                    if self.default_number != 0 {
+                        filename = format!("{}_{}", self.default_name, self.default_number);
+                        self.default_number += 1;
+                    } else {
+                        filename = self.default_name.clone();
+                        self.default_number += 1;
+                    }
+                }
+                vec![Effect::ChooseSavePath {
+                    location: self.default_location()?,
+                    filename,
+                }]
+            }
+            Action::SelectArea => {
+                self.selection_mode = Selection::Area;
+                self.selecting_area = false;
+                vec![]
+            }
+            Action::SelectFullscreen => {
+                self.selection_mode = Selection::Fullscreen;
+                self.selecting_area = false;
+                vec![]
+            }
+            Action::SelectAllScreens => {
+                self.selection_mode = Selection::AllScreens;
+                self.selecting_area = false;
+                vec![]
+            }
+            Action::Settings => {
+                self.show_settings = true;
+                vec![]
+            }
+            Action::SetTimer => {
+                self.timer.open_form();
+                vec![]
+            }
+            Action::StartTimer => {
+                if self.timer.seconds > 0 {
+                    self.timer.start_timer();
+                    vec![]
+                } else {
+                    self.dispatch(Action::Capture)?
+                }
+            }
+            Action::Redo => {
+                let (history, mut document) = self.document();
+                let image_changed = history.redo(&mut document);
+                image_effects(image_changed)
+            }
+            Action::Undo => {
+                let (history, mut document) = self.document();
+                let image_changed = history.undo(&mut document);
+                image_effects(image_changed)
+            }
+        };
+        Ok(effects)
+    }
+
+    // The saving path of the settings, or the default one if it does not exist
+    fn default_location(&self) -> Result<PathBuf, AppError> {
+        let current_dir = std::env::current_dir().map_err(|error| AppError::Io {
+            path: PathBuf::from("."),
+            error,
+        })?;
+        let location = current_dir.join(&self.default_path);
+        if location.exists() {
+            Ok(location)
+        } else {
+            Ok(current_dir.join("screenshots"))
+        }
+    }
+
+    // Takes the screenshot once the window is hidden. If it fails the window has to be
+    // shown again all the same. For an area shot the screens are frozen, and the area is
+    // selected on them before it is cut with `Action::Capture`
+    pub fn capture(&mut self) -> Result<Vec<Effect>, AppError> {
+        self.hide = false;
+        let image = match self.selection_mode {
+            Selection::Area => {
+                self.frozen = Some(self.screenshots.freeze()?);
+                self.selecting_area = true;
+                return Ok(vec![Effect::DesktopFrozen, Effect::ShowWindow]);
+            }
+            Selection::Fullscreen => self.screenshots.capture(None)?,
+            Selection::AllScreens => self.screenshots.capture_all()?,
+        };
+        self.show_image(image, None);
+        Ok(vec![Effect::ImageChanged, Effect::ShowWindow])
+    }
+
+    // The selected area of the frozen screens
+    fn capture_area(&mut self) -> Result<Vec<Effect>, AppError> {
+        let image = self
+            .frozen
+            .as_ref()
+            .and_then(|frozen| frozen.crop(self.area))
+            .ok_or_else(|| AppError::Capture {
+                screen: self.screenshots.screen_number,
+                area: Some(self.area),
+                message: "the area is outside of the screens".to_string(),
+            })?;
+        self.show_image(image, None);
+        Ok(vec![Effect::ImageChanged])
+    }
+
+    // Opens an image or, by its extension, a saved project in place of the current
+    // screenshot, after `Effect::ChooseOpenPath` or when a file is dropped on the window
+    pub fn open(&mut self, path: &Path) -> Result<Vec<Effect>, AppError> {
+        if project::is_project(path) {
+            return self.open_project(path);
+        }
+        let bytes = fs::read(path).map_err(|error| AppError::Io {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        let image = image::load_from_memory(&bytes).map_err(|err| AppError::Open {
+            path: path.to_path_buf(),
+            message: err.to_string(),
+        })?;
+        self.show_image(image.to_rgba8(), None);
+        Ok(vec![Effect::ImageChanged])
+    }
+
+    // Starts editing a new screenshot, without annotations
+    fn show_image(&mut self, original: RgbaImage, crop: Option<Rectangle>) {
+        self.image = Some(crop::crop_image(&original, crop));
+        self.original = Some(original);
+        self.crop = crop;
+        self.view_image = true;
+        self.selecting_area = false;
+        self.frozen = None;
+        self.modification = false;
+        self.show_settings = false;
+        self.modifier = Modifier::NotSelected;
+        self.zoom = Zoom::Fit;
+        self.annotations.clear();
+        self.history.clear();
+    }
+
+    // Opens a saved project in place of the current screenshot, with its annotations
+    // still editable
+    fn open_project(&mut self, path: &Path) -> Result<Vec<Effect>, AppError> {
+        let project = project::load(path).map_err(|err| AppError::open_project(path, err))?;
+        self.show_image(project.original, project.crop);
+        self.annotations.numbering = project.numbering;
+        for shape in project.shapes {
+            let annotation = self.annotations.create(shape);
+            self.annotations.insert(self.annotations.len(), annotation);
+        }
+        Ok(vec![Effect::ImageChanged])
+    }
+
+    fn document(&mut self) -> (&mut History, Document<'_>) {
+        (
+            &mut self.history,
+            Document {
+                annotations: &mut self.annotations,
+                image: &mut self.image,
+                original: self.original.as_ref(),
+                crop: &mut self.crop,
+            },
+        )
+    }
+
+    fn execute(&mut self, change: Change) {
+        let (history, mut document) = self.document();
+        history.execute(change, &mut document);
+    }
+
+    pub fn add_annotation(&mut self, shape: Shape) -> ShapeId {
+        let annotation = self.annotations.create(shape);
+        let id = annotation.id;
+        let index = self.annotations.len();
+        self.execute(Change::Add { index, annotation });
+        id
+    }
+
+    pub fn delete_annotation(&mut self, id: ShapeId) {
+        self.execute(Change::Delete(id));
+    }
+
+    pub fn move_annotation(&mut self, id: ShapeId, offset: Point) {
+        self.execute(Change::Move { id, offset });
+    }
+
+    // Replaces the annotation, e.g. when it is resized or its text is edited
+    pub fn reshape_annotation(&mut self, id: ShapeId, shape: Shape) {
+        if self.annotations.get(id) != Some(&shape) {
+            self.execute(Change::Reshape { id, shape });
+        }
+    }
+
+    pub fn restyle_annotation(&mut self, id: ShapeId, stroke: Stroke) {
+        let Some(shape) = self.annotations.get(id) else {
+            return;
+        };
+        let mut restyled = shape.clone();
+        restyled.set_stroke(stroke);
+        if restyled != *shape {
+            self.execute(Change::Restyle { id, stroke });
+        }
+    }
+
+    pub fn refill_annotation(&mut self, id: ShapeId, fill: Color, rounding: f32) {
+        let Some(shape) = self.annotations.get(id) else {
+            return;
+        };
+        let mut refilled = shape.clone();
+        refilled.set_fill(fill, rounding);
+        if refilled != *shape {
+            self.execute(Change::Refill { id, fill, rounding });
+        }
+    }
+
+    // Changes how the markers are labelled, unless the sequence has no label for the start
+    pub fn renumber(&mut self, numbering: Numbering) {
+        if numbering.is_valid() && numbering != self.annotations.numbering {
+            self.execute(Change::Renumber(numbering));
+        }
+    }
+
+    // Changes how a redaction hides the pixels under it
+    pub fn change_redaction(&mut self, id: ShapeId, redaction: Redaction) {
+        if let Some(Shape::Redact { start, end, .. }) = self.annotations.get(id) {
+            let shape = Shape::Redact {
+                start: *start,
+                end: *end,
+                redaction,
+            };
+            self.reshape_annotation(id, shape);
+        }
+    }
+
+    // The arrow style is ignored for the lines
+    pub fn change_line_style(&mut self, id: ShapeId, new_dash: Dash, new_style: ArrowStyle) {
+        let mut shape = match self.annotations.get(id) {
+            Some(shape @ (Shape::Line { .. } | Shape::Arrow { .. })) => shape.clone(),
+            _ => return,
+        };
+        match &mut shape {
+            Shape::Line { dash, .. } => *dash = new_dash,
+            Shape::Arrow { dash, style, .. } => {
+                *dash = new_dash;
+                *style = new_style;
+            }
+            _ => {}
+        }
+        self.reshape_annotation(id, shape);
+    }
+
+    // Keeps only the given area of the original screenshot, in its pixels. The original
+    // is kept, so the crop can be changed again later
+    pub fn crop(&mut self, area: Rectangle) -> Vec<Effect> {
+        let Some(original) = self.original.as_ref() else {
+            return vec![];
+        };
+        let Some(area) = crop::clamp(area, original.width(), original.height()) else {
+            return vec![];
+        };
+        let full = area.width as u32 == original.width() && area.height as u32 == original.height();
+        let area = if full { None } else { Some(area) };
+        if area == self.crop {
+            return vec![];
+        }
+        self.execute(Change::Crop(area));
+        vec![Effect::ImageChanged]
+    }
+
+    // Reverts every change made to the screenshot, they can still be redone
+    pub fn undo_all(&mut self) -> Vec<Effect> {
+        let mut image_changed = false;
+        let (history, mut document) = self.document();
+        while history.can_undo() {
+            image_changed |= history.undo(&mut document);
+        }
+        image_effects(image_changed)
+    }
+
+    // The screenshot with its annotations drawn at full resolution, as it is exported
+    pub fn rendered_image(&self) -> Option<RgbaImage> {
+        let mut image = self.image.clone()?;
+        rasterizer::burn_annotations(&mut image, &self.annotations);
+        Some(image)
+    }
+
+    // Whether saving to `path` would keep the pixels under the redactions. A project keeps
+    // the original screenshot as it was captured, so that the redactions stay editable
+    pub fn reveals_redactions(&self, path: &Path) -> bool {
+        project::is_project(path)
+            && self
+                .annotations
+                .shapes()
+                .any(|shape| matches!(shape, Shape::Redact { .. }))
+    }
+
+    // Saves the screenshot to the path chosen after `Effect::ChooseSavePath`. A project
+    // keeps the annotations editable, an SVG keeps them as vectors and the other formats
+    // have them drawn on the image
+    pub fn save_to(&self, path: &Path) -> Result<PathBuf, AppError> {
+        let mut path = path.to_path_buf();
+        if path.extension().is_none() {
+            path.set_extension("png");
+        }
+        if project::is_project(&path) {
+            let project = Project {
+                original: self.original.clone().ok_or(AppError::NoScreenshot)?,
+                crop: self.crop,
+                numbering: self.annotations.numbering,
+                shapes: self.annotations.shapes().cloned().collect(),
+            };
+            project::save(&project, &path).map_err(|err| AppError::save_project(&path, err))?;
+            return Ok(path);
+        }
+        if svg::is_svg(&path) {
+            let image = self.image.as_ref().ok_or(AppError::NoScreenshot)?;
+            let document = svg::render(image, &self.annotations)
+                .map_err(|err| AppError::export(&path, err))?;
+            fs::write(&path, document).map_err(|error| AppError::Io {
+                path: path.clone(),
+                error,
+            })?;
+            return Ok(path);
+        }
+        let image = self.rendered_image().ok_or(AppError::NoScreenshot)?;
+        export::save(&image, &path, &self.export_settings)
+            .map_err(|err| AppError::export(&path, err))?;
+        Ok(path)
+    }
+}
+
+fn image_effects(image_changed: bool) -> Vec<Effect> {
+    if image_changed {
+        vec![Effect::ImageChanged]
+    } else {
+        vec![]
+    }
+}
@@ -0,0 +1,274 @@
+use super::actions::Action;
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
+
+// Names of the keys that can be used in a shortcut
+pub const KEY_NAMES: [&str; 73] = [
+    "A",
+    "B",
+    "C",
+    "D",
+    "E",
+    "F",
+    "G",
+    "H",
+    "I",
+    "J",
+    "K",
+    "L",
+    "M",
+    "N",
+    "O",
+    "P",
+    "Q",
+    "R",
+    "S",
+    "T",
+    "U",
+    "V",
+    "W",
+    "X",
+    "Y",
+    "Z",
+    "0",
+    "1",
+    "2",
+    "3",
+    "4",
+    "5",
+    "6",
+    "7",
+    "8",
+    "9",
+    "Down",
+    "Left",
+    "Right",
+    "Up",
+    "Escape",
+    "Tab",
+    "Backspace",
+    "Enter",
+    "Space",
+    "Insert",
+    "Delete",
+    "Home",
+    "End",
+    "PageUp",
+    "PageDown",
+    "Minus",
+    "Plus",
+    "F1",
+    "F2",
+    "F3",
+    "F4",
+    "F5",
+    "F6",
+    "F7",
+    "F8",
+    "F9",
+    "F10",
+    "F11",
+    "F12",
+    "F13",
+    "F14",
+    "F15",
+    "F16",
+    "F17",
+    "F18",
+    "F19",
+    "F20",
+];
+
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub struct KeyboardShortcut {
+    pub alt: bool,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub mac_cmd: bool,
+    // Ctrl on Windows and Linux, Cmd on Mac
+    pub command: bool,
+    #[serde(deserialize_with = "deserialize_key")]
+    pub key: String,
+}
+
+fn deserialize_key<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    let key = String::deserialize(deserializer)?;
+    if KEY_NAMES.contains(&key.as_str()) {
+        Ok(key)
+    } else {
+        Err(D::Error::custom(format!("unknown key \"{}\"", key)))
+    }
+}
+
+impl KeyboardShortcut {
+    pub fn command(key: &str) -> Self {
+        Self {
+            command: true,
+            key: key.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ShortCut {
+    #[serde(skip)]
+    pub name: String,
+    pub description: String,
+    pub shortcut: KeyboardShortcut,
+    pub active: bool,
+    #[serde(skip)]
+    while_viewing_image: bool,
+    pub action: Action,
+}
+
+impl ShortCut {
+    fn new(shortcut: KeyboardShortcut, description: String, action: Action) -> Self {
+        Self {
+            name: action.to_string(),
+            description,
+            shortcut,
+            active: true,
+            while_viewing_image: action.can_be_performed_during_image_view(),
+            action,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct AllShortcuts {
+    pub vec: Vec<ShortCut>,
+}
+
+impl Default for AllShortcuts {
+    fn default() -> Self {
+        let vec = vec![
+            ShortCut::new(
+                KeyboardShortcut::command("C"),
+                "Copy to clipboard".to_string(),
+                Action::Copy,
+            ),
+            ShortCut::new(
+                KeyboardShortcut::command("H"),
+                "Go to the home page".to_string(),
+                Action::HomePage,
+            ),
+            ShortCut::new(
+                KeyboardShortcut::command("N"),
+                "Take a new screenshot".to_string(),
+                Action::NewScreenshot,
+            ),
+            ShortCut::new(
+                KeyboardShortcut::command("O"),
+                "Open an image or a project".to_string(),
+                Action::Open,
+            ),
+            ShortCut::new(
+                KeyboardShortcut::command("S"),
+                "Save".to_string(),
+                Action::Save,
+            ),
+            ShortCut::new(
+                KeyboardShortcut::command("W"),
+                "Close the application".to_string(),
+                Action::Close,
+            ),
+            ShortCut::new(
+                KeyboardShortcut::command("Z"),
+                "Undo".to_string(),
+                Action::Undo,
+            ),
+            ShortCut::new(
+                KeyboardShortcut {
+                    shift: true,
+                    ..KeyboardShortcut::command("Z")
+                },
+                "Redo".to_string(),
+                Action::Redo,
+            ),
+            ShortCut::new(
+                KeyboardShortcut::command("M"),
+                "Modify the screenshot".to_string(),
+                Action::Modify,
+            ),
+            ShortCut::new(
+                KeyboardShortcut::command("R"),
+                "Reset the timer".to_string(),
+                Action::ResetTimer,
+            ),
+            ShortCut::new(
+                KeyboardShortcut::command("T"),
+                "Set the timer".to_string(),
+                Action::SetTimer,
+            ),
+            ShortCut::new(
+                KeyboardShortcut::command("U"),
+                "Start the timer".to_string(),
+                Action::StartTimer,
+            ),
+            ShortCut::new(
+                KeyboardShortcut::command("Q"),
+                "Open the settings menu".to_string(),
+                Action::Settings,
+            ),
+        ];
+
+        Self { vec }
+    }
+}
+
+impl AllShortcuts {
+    // Builds the shortcuts from the ones stored in the settings file: actions missing from
+    // the file (e.g. added in a newer version) keep their default combination
+    pub fn from_saved(saved: Vec<ShortCut>) -> Self {
+        let mut shortcuts = AllShortcuts::default();
+        for shortcut in shortcuts.vec.iter_mut() {
+            if let Some(saved) = saved.iter().find(|saved| saved.action == shortcut.action) {
+                shortcut.description = saved.description.clone();
+                shortcut.shortcut = saved.shortcut.clone();
+                shortcut.active = saved.active;
+            }
+        }
+        shortcuts
+    }
+
+    pub fn is_default(shortcut: &AllShortcuts) -> bool {
+        let default_shortcuts = AllShortcuts::default();
+        for sc in default_shortcuts.vec {
+            if !shortcut.vec.contains(&sc) {
+                return false;
+            }
+        }
+        true
+    }
+
+    // The shortcuts that can be pressed in the current state of the application
+    pub fn enabled(
+        &self,
+        image_viewing: bool,
+        selecting_area: bool,
+    ) -> impl Iterator<Item = &ShortCut> {
+        self.vec.iter().filter(move |shortcut| {
+            if shortcut.action == Action::Settings
+                || shortcut.action == Action::Close
+                || (shortcut.action == Action::HomePage && selecting_area)
+            {
+                shortcut.active
+            } else {
+                //Shortcuts that can be pressed when viewing the image
+                shortcut.while_viewing_image == image_viewing && shortcut.active
+            }
+        })
+    }
+
+    // Check for duplicate shortcuts
+    pub fn has_duplicate_shortcuts(&self) -> bool {
+        for (i, shortcut1) in self.vec.iter().enumerate() {
+            for shortcut2 in self.vec.iter().skip(i + 1) {
+                if shortcut1.shortcut == shortcut2.shortcut {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
@@ -0,0 +1,426 @@
+use super::{
+    annotation::{Annotations, Color, Point, Shape, Stroke},
+    export::{self, ExportError, ExportFormat, ExportSettings},
+    path, rasterizer, text,
+};
+use ab_glyph::{Font, FontRef, PxScale, ScaleFont};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use image::{imageops, RgbaImage};
+use std::{fmt::Write, path::Path};
+
+pub const SVG_EXTENSION: &str = "svg";
+pub const SVG_NAME: &str = "SVG Image";
+
+// Family of the font of the marker labels in the SVG
+const MARKER_FONT: &str = "Ubuntu-Light";
+
+pub fn is_svg(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case(SVG_EXTENSION))
+}
+
+// The screenshot with the annotations drawn over it as vector shapes, in the pixels of the
+// screenshot, looking like the raster export. The redactions and what they cover cannot be
+// vectors: they are burned in the embedded screenshot, so that the hidden pixels are not
+// in the file, and painted again as images where they are in the stack. The arrow heads
+// are `<marker>`s of the line they end, with the geometry of the raster export
+pub fn render(image: &RgbaImage, annotations: &Annotations) -> Result<String, ExportError> {
+    let (width, height) = image.dimensions();
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+    )
+    .unwrap();
+
+    let fonts = used_fonts(annotations);
+    if !fonts.is_empty() {
+        svg.push_str("<defs><style>\n");
+        for (name, data) in fonts {
+            writeln!(
+                svg,
+                r#"@font-face {{ font-family: "{}"; src: url(data:font/ttf;base64,{}); }}"#,
+                name,
+                STANDARD.encode(data)
+            )
+            .unwrap();
+        }
+        svg.push_str("</style></defs>\n");
+    }
+
+    let mut redacted = image.clone();
+    for shape in annotations.shapes() {
+        if let Some((x, y, pixels)) = redaction(image, shape) {
+            imageops::replace(&mut redacted, &pixels, x as i64, y as i64);
+        }
+    }
+    svg.push_str(&image_element(0, 0, &redacted)?);
+
+    // The annotations drawn so far, to render the redactions from what they cover
+    let mut below = Annotations::default();
+    below.numbering = annotations.numbering;
+    let mut markers = 0;
+    let mut heads = 0;
+    for shape in annotations.shapes() {
+        match shape {
+            Shape::Pen { points, stroke } => {
+                if points.len() >= 2 {
+                    writeln!(
+                        svg,
+                        r#"<path d="{}" fill="none" {} stroke-linecap="round" stroke-linejoin="round"/>"#,
+                        path_data(points),
+                        stroke_attributes(*stroke)
+                    )
+                    .unwrap();
+                }
+            }
+            // Multiplying the colors under the stroke is the same as the raster export
+            Shape::Highlight { points, stroke } => {
+                if points.len() >= 2 {
+                    writeln!(
+                        svg,
+                        r#"<path d="{}" fill="none" {} stroke-linecap="round" stroke-linejoin="round" style="mix-blend-mode:multiply"/>"#,
+                        path_data(points),
+                        stroke_attributes(*stroke)
+                    )
+                    .unwrap();
+                }
+            }
+            // The line and the heads are opaque in a translucent group, so that they are
+            // blended once where they overlap
+            Shape::Line { stroke, .. } | Shape::Arrow { stroke, .. } => {
+                let Some(line_path) = path::line_path(shape) else {
+                    continue;
+                };
+                let opaque = Stroke::new(
+                    stroke.width,
+                    Color {
+                        a: 255,
+                        ..stroke.color
+                    },
+                );
+                writeln!(svg, r#"<g opacity="{}">"#, opacity(stroke.color)).unwrap();
+                let points = match line_path.points.as_slice() {
+                    [point] => vec![*point, *point],
+                    points => points.to_vec(),
+                };
+                let dash = match shape {
+                    Shape::Line { dash, .. } | Shape::Arrow { dash, .. } => {
+                        path::dash_pattern(*dash, stroke.width)
+                    }
+                    _ => None,
+                };
+                // The first head is on the end of the line and the second on its start
+                let mut ends = Vec::new();
+                for (head, end) in line_path.heads.iter().zip(["end", "start"]) {
+                    heads += 1;
+                    let vertex = if end == "end" {
+                        points[points.len() - 1]
+                    } else {
+                        points[0]
+                    };
+                    svg.push_str(&head_marker(heads, head, vertex, opaque));
+                    ends.push(format!(r#" marker-{}="url(#head{})""#, end, heads));
+                }
+                if !line_path.pieces.is_empty() || !ends.is_empty() {
+                    let stroke = if line_path.pieces.is_empty() {
+                        r#"stroke="none""#.to_string()
+                    } else {
+                        stroke_attributes(opaque)
+                    };
+                    writeln!(
+                        svg,
+                        r#"<path d="{}" fill="none" {} stroke-linecap="round" stroke-linejoin="round"{}{}/>"#,
+                        path_data(&points),
+                        stroke,
+                        dash.map(|(on, off)| format!(r#" stroke-dasharray="{} {}""#, on, off))
+                            .unwrap_or_default(),
+                        ends.concat()
+                    )
+                    .unwrap();
+                }
+                svg.push_str("</g>\n");
+            }
+            Shape::Rect {
+                start,
+                end,
+                stroke,
+                fill,
+                rounding,
+            } => {
+                let (min, max) = (start.min(*end), start.max(*end));
+                let size = max - min;
+                let rounding = rounding.clamp(0.0, size.x.min(size.y) / 2.0);
+                writeln!(
+                    svg,
+                    r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}" {} {}/>"#,
+                    min.x,
+                    min.y,
+                    size.x,
+                    size.y,
+                    rounding,
+                    paint("fill", *fill),
+                    stroke_attributes(*stroke)
+                )
+                .unwrap();
+            }
+            Shape::Circle {
+                center,
+                radius,
+                stroke,
+                fill,
+            } => {
+                writeln!(
+                    svg,
+                    r#"<circle cx="{}" cy="{}" r="{}" {} {}/>"#,
+                    center.x,
+                    center.y,
+                    radius,
+                    paint("fill", *fill),
+                    stroke_attributes(*stroke)
+                )
+                .unwrap();
+            }
+            Shape::Ellipse {
+                start,
+                end,
+                stroke,
+                fill,
+            } => {
+                let (min, max) = (start.min(*end), start.max(*end));
+                let center = (min + max) * 0.5;
+                let radii = (max - min) * 0.5;
+                writeln!(
+                    svg,
+                    r#"<ellipse cx="{}" cy="{}" rx="{}" ry="{}" {} {}/>"#,
+                    center.x,
+                    center.y,
+                    radii.x,
+                    radii.y,
+                    paint("fill", *fill),
+                    stroke_attributes(*stroke)
+                )
+                .unwrap();
+            }
+            Shape::Text { pos, text, style } => {
+                let layout = text::layout(text, style);
+                if style.background.a > 0 {
+                    writeln!(
+                        svg,
+                        r#"<rect x="{}" y="{}" width="{}" height="{}" {}/>"#,
+                        pos.x,
+                        pos.y,
+                        layout.size.x,
+                        layout.size.y,
+                        paint("fill", style.background)
+                    )
+                    .unwrap();
+                }
+                let font = style.font();
+                for (offset, line) in layout.lines {
+                    svg.push_str(&text_element(
+                        &font,
+                        style.font_name(),
+                        *pos + offset,
+                        &line,
+                        style.size,
+                        style.color,
+                        0.0,
+                    ));
+                }
+            }
+            Shape::Marker {
+                center,
+                radius,
+                color,
+            } => {
+                writeln!(
+                    svg,
+                    r#"<circle cx="{}" cy="{}" r="{}" {}/>"#,
+                    center.x,
+                    center.y,
+                    radius,
+                    paint("fill", *color)
+                )
+                .unwrap();
+                let label = annotations.numbering.label(markers);
+                let (top_left, size, text_color) =
+                    rasterizer::marker_label(*center, *radius, &label, *color);
+                let font = FontRef::try_from_slice(rasterizer::TEXT_FONT).unwrap();
+                svg.push_str(&text_element(
+                    &font,
+                    MARKER_FONT,
+                    top_left,
+                    &label,
+                    size,
+                    text_color,
+                    size * rasterizer::TEXT_BASELINE_OFFSET_FACTOR,
+                ));
+                markers += 1;
+            }
+            // What the raster export has there, the annotations under it included
+            Shape::Redact { .. } => {
+                let mut rendered = image.clone();
+                rasterizer::burn_annotations(&mut rendered, &below);
+                if let Some((x, y, pixels)) = redaction(&rendered, shape) {
+                    svg.push_str(&image_element(x, y, &pixels)?);
+                }
+            }
+        }
+        let annotation = below.create(shape.clone());
+        below.insert(below.len(), annotation);
+    }
+    svg.push_str("</svg>\n");
+    Ok(svg)
+}
+
+// The `<marker>` of an arrow head, in the coordinates of the image: not rotated and with
+// the vertex of the line it goes on as its reference point, so that it is exactly where
+// the raster export draws it, even if the line stops inside of a filled head
+fn head_marker(id: usize, head: &path::Head, vertex: Point, stroke: Stroke) -> String {
+    let points = points_list(&[head.sides[0], head.tip, head.sides[1]]);
+    let head = if head.filled {
+        format!(
+            r#"<polygon points="{}" {}/>"#,
+            points,
+            paint("fill", stroke.color)
+        )
+    } else {
+        format!(
+            r#"<polyline points="{}" fill="none" {} stroke-linecap="round" stroke-linejoin="round"/>"#,
+            points,
+            stroke_attributes(stroke)
+        )
+    };
+    let mut marker = String::new();
+    writeln!(
+        marker,
+        r#"<defs><marker id="head{}" markerUnits="userSpaceOnUse" orient="0" refX="{}" refY="{}" markerWidth="1" markerHeight="1" overflow="visible">{}</marker></defs>"#,
+        id, vertex.x, vertex.y, head
+    )
+    .unwrap();
+    marker
+}
+
+// The pixels of a redaction over `image`, with the position of their top left corner
+fn redaction(image: &RgbaImage, shape: &Shape) -> Option<(u32, u32, RgbaImage)> {
+    match shape {
+        Shape::Redact { .. } => rasterizer::rendered(image, shape),
+        _ => None,
+    }
+}
+
+// The fonts of the texts and of the markers, embedded so that the text looks like in the
+// raster export on any system
+fn used_fonts(annotations: &Annotations) -> Vec<(&'static str, &'static [u8])> {
+    let mut fonts: Vec<(&'static str, &'static [u8])> = Vec::new();
+    for shape in annotations.shapes() {
+        let font = match shape {
+            Shape::Text { style, .. } => text::fonts().find(|(name, _)| *name == style.font_name()),
+            Shape::Marker { .. } => Some((MARKER_FONT, rasterizer::TEXT_FONT)),
+            _ => None,
+        };
+        if let Some(font) = font {
+            if !fonts.iter().any(|(name, _)| *name == font.0) {
+                fonts.push(font);
+            }
+        }
+    }
+    fonts
+}
+
+fn image_element(x: u32, y: u32, image: &RgbaImage) -> Result<String, ExportError> {
+    let png = export::encode(image, ExportFormat::Png, &ExportSettings::default())?;
+    Ok(format!(
+        "<image x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" href=\"data:image/png;base64,{}\"/>\n",
+        x,
+        y,
+        image.width(),
+        image.height(),
+        STANDARD.encode(png)
+    ))
+}
+
+// A line of text whose box has its top left corner at `top_left`, placed like the
+// rasterizer places it. `size` is the height of the font as ab_glyph scales it, which is
+// not the em size of CSS
+fn text_element(
+    font: &FontRef,
+    family: &str,
+    top_left: Point,
+    line: &str,
+    size: f32,
+    color: Color,
+    baseline_offset: f32,
+) -> String {
+    let scaled = font.as_scaled(PxScale::from(size));
+    let font_size = size * font.units_per_em().unwrap_or(1.0) / font.height_unscaled();
+    format!(
+        "<text x=\"{}\" y=\"{}\" font-family=\"{}\" font-size=\"{}\" {} xml:space=\"preserve\">{}</text>\n",
+        top_left.x,
+        top_left.y + scaled.ascent() + baseline_offset,
+        family,
+        font_size,
+        paint("fill", color),
+        escape(line)
+    )
+}
+
+fn path_data(points: &[Point]) -> String {
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| format!("{}{} {}", if i == 0 { "M" } else { "L" }, p.x, p.y))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn points_list(points: &[Point]) -> String {
+    points
+        .iter()
+        .map(|p| format!("{},{}", p.x, p.y))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn opacity(color: Color) -> f32 {
+    color.a as f32 / 255.0
+}
+
+// `attribute` painted with the color, e.g. `fill`
+fn paint(attribute: &str, color: Color) -> String {
+    match color.a {
+        0 => format!("{}=\"none\"", attribute),
+        255 => format!(
+            "{}=\"#{:02x}{:02x}{:02x}\"",
+            attribute, color.r, color.g, color.b
+        ),
+        _ => format!(
+            "{}=\"#{:02x}{:02x}{:02x}\" {}-opacity=\"{}\"",
+            attribute,
+            color.r,
+            color.g,
+            color.b,
+            attribute,
+            opacity(color)
+        ),
+    }
+}
+
+fn stroke_attributes(stroke: Stroke) -> String {
+    if stroke.width <= 0.0 {
+        return "stroke=\"none\"".to_string();
+    }
+    format!(
+        "{} stroke-width=\"{}\"",
+        paint("stroke", stroke.color),
+        stroke.width
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,216 @@
+use super::annotation::{Color, Point};
+use ab_glyph::{Font, FontRef, PxScale, ScaleFont};
+use serde::{Deserialize, Serialize};
+
+// The fonts of the text annotations, bundled so that the exported text looks the same on
+// every system. Each one is a family with its bold and italic variants
+const FONTS: [(&str, &[u8]); 12] = [
+    (
+        "DejaVuSans",
+        include_bytes!("../../assets/fonts/DejaVuSans.ttf"),
+    ),
+    (
+        "DejaVuSans-Bold",
+        include_bytes!("../../assets/fonts/DejaVuSans-Bold.ttf"),
+    ),
+    (
+        "DejaVuSans-Oblique",
+        include_bytes!("../../assets/fonts/DejaVuSans-Oblique.ttf"),
+    ),
+    (
+        "DejaVuSans-BoldOblique",
+        include_bytes!("../../assets/fonts/DejaVuSans-BoldOblique.ttf"),
+    ),
+    (
+        "DejaVuSerif",
+        include_bytes!("../../assets/fonts/DejaVuSerif.ttf"),
+    ),
+    (
+        "DejaVuSerif-Bold",
+        include_bytes!("../../assets/fonts/DejaVuSerif-Bold.ttf"),
+    ),
+    (
+        "DejaVuSerif-Italic",
+        include_bytes!("../../assets/fonts/DejaVuSerif-Italic.ttf"),
+    ),
+    (
+        "DejaVuSerif-BoldItalic",
+        include_bytes!("../../assets/fonts/DejaVuSerif-BoldItalic.ttf"),
+    ),
+    (
+        "DejaVuSansMono",
+        include_bytes!("../../assets/fonts/DejaVuSansMono.ttf"),
+    ),
+    (
+        "DejaVuSansMono-Bold",
+        include_bytes!("../../assets/fonts/DejaVuSansMono-Bold.ttf"),
+    ),
+    (
+        "DejaVuSansMono-Oblique",
+        include_bytes!("../../assets/fonts/DejaVuSansMono-Oblique.ttf"),
+    ),
+    (
+        "DejaVuSansMono-BoldOblique",
+        include_bytes!("../../assets/fonts/DejaVuSansMono-BoldOblique.ttf"),
+    ),
+];
+
+// Name and data of every bundled font, for the front end to load them too
+pub fn fonts() -> impl Iterator<Item = (&'static str, &'static [u8])> {
+    FONTS.into_iter()
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum FontFamily {
+    Sans,
+    Serif,
+    Mono,
+}
+
+impl FontFamily {
+    pub fn all() -> [FontFamily; 3] {
+        [FontFamily::Sans, FontFamily::Serif, FontFamily::Mono]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FontFamily::Sans => "Sans",
+            FontFamily::Serif => "Serif",
+            FontFamily::Mono => "Mono",
+        }
+    }
+}
+
+// How the lines of a text are aligned to each other
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Alignment {
+    Left,
+    Center,
+    Right,
+}
+
+impl Alignment {
+    pub fn all() -> [Alignment; 3] {
+        [Alignment::Left, Alignment::Center, Alignment::Right]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Alignment::Left => "Left",
+            Alignment::Center => "Center",
+            Alignment::Right => "Right",
+        }
+    }
+}
+
+// How a text annotation looks. Sizes are in pixels of the screenshot
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct TextStyle {
+    pub size: f32,
+    pub family: FontFamily,
+    pub bold: bool,
+    pub italic: bool,
+    pub alignment: Alignment,
+    pub color: Color,
+    // The box behind the text, transparent for none, and how far it goes around the text
+    pub background: Color,
+    pub padding: f32,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        Self {
+            size: 24.0,
+            family: FontFamily::Sans,
+            bold: false,
+            italic: false,
+            alignment: Alignment::Left,
+            color: Color::BLACK,
+            background: Color::TRANSPARENT,
+            padding: 6.0,
+        }
+    }
+}
+
+impl TextStyle {
+    // The name of the font among `fonts`
+    pub fn font_name(self) -> &'static str {
+        FONTS[self.font_index()].0
+    }
+
+    pub fn font(self) -> FontRef<'static> {
+        FontRef::try_from_slice(FONTS[self.font_index()].1).unwrap()
+    }
+
+    fn font_index(self) -> usize {
+        let family = match self.family {
+            FontFamily::Sans => 0,
+            FontFamily::Serif => 4,
+            FontFamily::Mono => 8,
+        };
+        family + self.bold as usize + 2 * self.italic as usize
+    }
+}
+
+// Where the lines of a text go, relative to the top left corner of its box
+pub struct TextLayout {
+    // Size of the box, padding included
+    pub size: Point,
+    // Top left corner of each line, with the line
+    pub lines: Vec<(Point, String)>,
+}
+
+pub fn layout(text: &str, style: &TextStyle) -> TextLayout {
+    let font = style.font();
+    let height = line_height(&font, style.size);
+    let lines: Vec<(f32, &str)> = text
+        .lines()
+        .map(|line| (line_width(&font, style.size, line), line))
+        .collect();
+    let width = lines
+        .iter()
+        .fold(0.0f32, |width, (line, _)| width.max(*line));
+    let factor = match style.alignment {
+        Alignment::Left => 0.0,
+        Alignment::Center => 0.5,
+        Alignment::Right => 1.0,
+    };
+    let padding = style.padding.max(0.0);
+    TextLayout {
+        size: Point::new(
+            width + 2.0 * padding,
+            height * lines.len().max(1) as f32 + 2.0 * padding,
+        ),
+        lines: lines
+            .into_iter()
+            .enumerate()
+            .map(|(i, (line_width, line))| {
+                let offset = Point::new(
+                    padding + (width - line_width) * factor,
+                    padding + height * i as f32,
+                );
+                (offset, line.to_string())
+            })
+            .collect(),
+    }
+}
+
+pub fn line_height(font: &impl Font, size: f32) -> f32 {
+    let scaled = font.as_scaled(PxScale::from(size));
+    scaled.ascent() - scaled.descent() + scaled.line_gap()
+}
+
+pub fn line_width(font: &impl Font, size: f32, line: &str) -> f32 {
+    let scaled = font.as_scaled(PxScale::from(size));
+    let mut caret = 0.0;
+    let mut previous = None;
+    for character in line.chars() {
+        let glyph_id = font.glyph_id(character);
+        if let Some(previous) = previous {
+            caret += scaled.kern(previous, glyph_id);
+        }
+        caret += scaled.h_advance(glyph_id);
+        previous = Some(glyph_id);
+    }
+    caret
+}
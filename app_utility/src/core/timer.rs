@@ -7,6 +7,12 @@ pub struct Timer {
     pub start_instant: Option<Instant>,
 }
 
+impl Default for Timer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Timer {
     pub fn new() -> Self {
         Timer {
@@ -48,4 +54,4 @@ impl Timer {
     pub fn is_running(&self) -> bool {
         self.running
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,101 @@
+use super::annotation::Point;
+
+// The most the viewer magnifies the screenshot, in points of the viewer per pixel
+pub const MAX_SCALE: f32 = 32.0;
+
+// How the screenshot is shown in the viewer. The sizes are in points of the viewer and the
+// positions in the viewer are relative to its top left corner
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum Zoom {
+    // The whole screenshot fits the viewer
+    #[default]
+    Fit,
+    // `scale` points of the viewer for each pixel of the screenshot, with the pixel
+    // `center` of the screenshot in the middle of the viewer
+    Scaled {
+        scale: f32,
+        center: Point,
+    },
+}
+
+// Points of the viewer per pixel of the screenshot when the whole screenshot fits in it
+pub fn fit_scale(image: Point, viewer: Point) -> f32 {
+    (viewer.x / image.x).min(viewer.y / image.y)
+}
+
+impl Zoom {
+    pub fn scale(self, image: Point, viewer: Point) -> f32 {
+        match self {
+            Zoom::Fit => fit_scale(image, viewer),
+            Zoom::Scaled { scale, .. } => scale,
+        }
+    }
+
+    // Where the top left corner of the screenshot is in the viewer
+    pub fn origin(self, image: Point, viewer: Point) -> Point {
+        match self {
+            Zoom::Fit => (viewer - image * fit_scale(image, viewer)) * 0.5,
+            Zoom::Scaled { scale, center } => viewer * 0.5 - center * scale,
+        }
+    }
+
+    // The pixel of the screenshot at `pos` in the viewer
+    pub fn to_image(self, pos: Point, image: Point, viewer: Point) -> Point {
+        (pos - self.origin(image, viewer)) * (1.0 / self.scale(image, viewer))
+    }
+
+    // The zoom with `scale`, keeping the pixel at `anchor` in the viewer where it is
+    pub fn scaled_at(self, scale: f32, anchor: Point, image: Point, viewer: Point) -> Zoom {
+        let pixel = self.to_image(anchor, image, viewer);
+        Zoom::Scaled {
+            scale,
+            center: pixel + (viewer * 0.5 - anchor) * (1.0 / scale),
+        }
+        .clamped(image, viewer)
+    }
+
+    // Magnified by `factor` around `anchor`, like with the mouse wheel under the pointer
+    pub fn zoomed_at(self, factor: f32, anchor: Point, image: Point, viewer: Point) -> Zoom {
+        self.scaled_at(self.scale(image, viewer) * factor, anchor, image, viewer)
+    }
+
+    // The screenshot dragged by `delta` points of the viewer
+    pub fn panned(self, delta: Point, image: Point, viewer: Point) -> Zoom {
+        match self {
+            Zoom::Fit => Zoom::Fit,
+            Zoom::Scaled { scale, center } => Zoom::Scaled {
+                scale,
+                center: center - delta * (1.0 / scale),
+            }
+            .clamped(image, viewer),
+        }
+    }
+
+    // The screenshot is never made smaller than fitting the viewer, unless that enlarges
+    // it, and the viewer stays inside of the screenshot along the sides it does not fit in
+    fn clamped(self, image: Point, viewer: Point) -> Zoom {
+        let Zoom::Scaled { scale, center } = self else {
+            return self;
+        };
+        let fit = fit_scale(image, viewer);
+        let scale = scale.clamp(fit.min(1.0), MAX_SCALE.max(fit));
+        if scale == fit {
+            return Zoom::Fit;
+        }
+        let clamp = |center: f32, viewer: f32, image: f32| {
+            let half = viewer / scale / 2.0;
+            if half * 2.0 >= image {
+                image / 2.0
+            } else {
+                center.clamp(half, image - half)
+            }
+        };
+        Zoom::Scaled {
+            scale,
+            center: Point::new(
+                clamp(center.x, viewer.x, image.x),
+                clamp(center.y, viewer.y, image.y),
+            ),
+        }
+    }
+}
@@ -0,0 +1,285 @@
+use super::{crop_corners, load_texture, view, CropDrag};
+use app_utility::core::{
+    annotation::Point,
+    capture::{FrozenDesktop, Pixel, Rectangle},
+    crop::{self, AspectRatio},
+};
+use eframe::egui::{self, Color32, Painter, Pos2, Rect, TextureHandle};
+
+// Pixels on each side of the one under the pointer shown in the loupe
+const LOUPE_RADIUS: i64 = 7;
+// Points of the window for each pixel in the loupe
+const LOUPE_ZOOM: f32 = 8.0;
+
+// The screens frozen when the area shot was taken, shown over the whole window while an
+// area is selected on them
+pub struct AreaSelection {
+    // Each screen where it is on the desktop, in points
+    screens: Vec<(Rectangle, TextureHandle)>,
+    // How the area follows the pointer, in points of the window
+    drag: Option<CropDrag>,
+    // The corner moved by the arrow keys, the last one that followed the pointer
+    corner: usize,
+}
+
+impl AreaSelection {
+    pub fn new(ctx: &egui::Context, frozen: &FrozenDesktop) -> Self {
+        Self {
+            screens: frozen
+                .screens
+                .iter()
+                .enumerate()
+                .map(|(i, (info, image))| {
+                    (
+                        info.bounds(),
+                        load_texture(ctx, &format!("frozen_screen_{}", i), image),
+                    )
+                })
+                .collect(),
+            drag: None,
+            // The bottom right one
+            corner: 2,
+        }
+    }
+
+    // Lets `area`, on the desktop in points, be drawn, moved and resized like the crop
+    // area, with its corners on the boundaries between the pixels of the screens. `origin`
+    // is where the window is on the desktop
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        frozen: &FrozenDesktop,
+        area: &mut Rectangle,
+        origin: Pos2,
+    ) {
+        let to_window = |area: Rectangle| {
+            Rect::from_min_size(
+                egui::pos2(area.x - origin.x, area.y - origin.y),
+                egui::vec2(area.width, area.height),
+            )
+        };
+        let to_desktop = |pos: Point| Point::new(pos.x + origin.x, pos.y + origin.y);
+        // A point of the window moved by whole pixels of the screen under it
+        let nudge = |pos: Point, pixels: Point| {
+            let point = frozen.nudge(to_desktop(pos), pixels);
+            Point::new(point.x - origin.x, point.y - origin.y)
+        };
+        egui::Area::new("frozen_desktop")
+            .order(egui::Order::Background)
+            .fixed_pos(Pos2::ZERO)
+            .show(ctx, |ui| {
+                let full = ctx.screen_rect();
+                let response = ui.allocate_rect(full, egui::Sense::drag());
+                let painter = ui.painter();
+                let uv = Rect::from_min_max(Pos2::ZERO, egui::pos2(1.0, 1.0));
+                for (bounds, texture) in &self.screens {
+                    painter.image(texture.id(), to_window(*bounds), uv, Color32::WHITE);
+                }
+
+                let (width, height) = (full.width() as u32, full.height() as u32);
+                let selected = to_window(*area);
+                let local = Rectangle {
+                    x: selected.min.x,
+                    y: selected.min.y,
+                    ..*area
+                };
+                let corners = crop_corners(local);
+                let inside = |pos: Point| selected.contains(egui::pos2(pos.x, pos.y));
+                let near = |pos: Point| {
+                    corners
+                        .iter()
+                        .position(|corner| corner.distance(pos) <= 8.0)
+                };
+
+                if let Some(hover_pos) = response.hover_pos() {
+                    let pos = Point::new(hover_pos.x, hover_pos.y);
+                    let cursor = if near(pos).is_none() && inside(pos) {
+                        egui::output::CursorIcon::Move
+                    } else {
+                        egui::output::CursorIcon::Crosshair
+                    };
+                    response.clone().on_hover_cursor(cursor);
+                }
+
+                // The new area, with where the corner that follows the pointer or the keys
+                // went
+                let mut moved = None;
+                if let Some(pointer_pos) = response.interact_pointer_pos() {
+                    let pos = nudge(Point::new(pointer_pos.x, pointer_pos.y), Point::default());
+                    match self.drag {
+                        // A corner resizes the area, inside it is moved and outside a new
+                        // one is drawn
+                        None => {
+                            self.drag = Some(match near(pos) {
+                                Some(corner) => CropDrag::Corner {
+                                    fixed: corners[(corner + 2) % 4],
+                                },
+                                None if inside(pos) => CropDrag::Move {
+                                    from: pos,
+                                    area: local,
+                                },
+                                None => CropDrag::Corner { fixed: pos },
+                            });
+                        }
+                        Some(CropDrag::Corner { fixed }) => {
+                            let area =
+                                crop::drag_corner(fixed, pos, AspectRatio::Free, width, height);
+                            moved = Some((area, Some(pos)));
+                        }
+                        Some(CropDrag::Move { from, area }) => {
+                            moved = Some((crop::move_by(area, pos - from, width, height), None));
+                        }
+                    }
+                } else {
+                    self.drag = None;
+                }
+
+                // The arrow keys move the edges at the corner by a pixel, or ten with shift
+                if moved.is_none() && local.width > 0.0 && local.height > 0.0 {
+                    let pixels = ctx.input(|i| {
+                        let step = if i.modifiers.shift { 10.0 } else { 1.0 };
+                        let pressed = |key| if i.key_pressed(key) { step } else { 0.0 };
+                        Point::new(
+                            pressed(egui::Key::ArrowRight) - pressed(egui::Key::ArrowLeft),
+                            pressed(egui::Key::ArrowDown) - pressed(egui::Key::ArrowUp),
+                        )
+                    });
+                    if pixels != Point::default() {
+                        let fixed = corners[(self.corner + 2) % 4];
+                        let to = nudge(corners[self.corner], pixels);
+                        let area = crop::drag_corner(fixed, to, AspectRatio::Free, width, height);
+                        moved = Some((area, Some(to)));
+                    }
+                }
+
+                if let Some((moved, to)) = moved {
+                    // The corner changes when it crosses the opposite one
+                    if let Some(to) = to {
+                        let corners = crop_corners(moved);
+                        self.corner = (0..4)
+                            .min_by(|a, b| {
+                                corners[*a]
+                                    .distance(to)
+                                    .total_cmp(&corners[*b].distance(to))
+                            })
+                            .unwrap_or(2);
+                    }
+                    *area = Rectangle {
+                        x: moved.x + origin.x,
+                        y: moved.y + origin.y,
+                        ..moved
+                    };
+                }
+
+                let selected = to_window(*area);
+                let size = frozen.size(*area);
+                view::paint_area(painter, full, selected);
+                // The size of the screenshot, in the pixels of the screens
+                if let Some((width, height)) = size {
+                    let galley = painter.layout_no_wrap(
+                        format!("{} × {}", width, height),
+                        egui::FontId::proportional(14.0),
+                        Color32::WHITE,
+                    );
+                    let label_size = galley.size() + egui::vec2(12.0, 6.0);
+                    // Above the area, or inside of it at the top of the window
+                    let mut label = Rect::from_min_size(
+                        selected.left_top() - egui::vec2(0.0, label_size.y + 6.0),
+                        label_size,
+                    );
+                    if label.top() < full.top() {
+                        label = label.translate(egui::vec2(6.0, label_size.y + 12.0));
+                    }
+                    painter.rect_filled(label, 4.0, Color32::from_black_alpha(180));
+                    painter.galley(label.min + egui::vec2(6.0, 3.0), galley);
+                }
+
+                let pointer = response.hover_pos().or(response.interact_pointer_pos());
+                if let Some(pointer) = pointer {
+                    let point = to_desktop(Point::new(pointer.x, pointer.y));
+                    if let Some(pixel) = frozen.pixel_at(point) {
+                        paint_loupe(painter, frozen, pixel, pointer, full, size);
+                    }
+                }
+            });
+    }
+}
+
+// The pixels around the pointer enlarged in a grid, with the position and the color of
+// the one under it and the size of the area
+fn paint_loupe(
+    painter: &Painter,
+    frozen: &FrozenDesktop,
+    pixel: Pixel,
+    pointer: Pos2,
+    full: Rect,
+    size: Option<(u32, u32)>,
+) {
+    let color = pixel.color;
+    let mut text = format!(
+        "{}, {}\n#{:02X}{:02X}{:02X}  rgb({}, {}, {})",
+        pixel.absolute.0, pixel.absolute.1, color.r, color.g, color.b, color.r, color.g, color.b
+    );
+    if let Some((width, height)) = size {
+        text += &format!("\n{} × {}", width, height);
+    }
+    let galley = painter.layout_no_wrap(text, egui::FontId::monospace(12.0), Color32::WHITE);
+    let side = (LOUPE_RADIUS * 2 + 1) as f32 * LOUPE_ZOOM;
+    let panel_size = egui::vec2(
+        side.max(galley.size().x) + 12.0,
+        side + galley.size().y + 18.0,
+    );
+    // Beside the pointer, on the side where it fits in the window
+    let offset = 24.0;
+    let mut min = pointer + egui::vec2(offset, offset);
+    if min.x + panel_size.x > full.max.x {
+        min.x = pointer.x - offset - panel_size.x;
+    }
+    if min.y + panel_size.y > full.max.y {
+        min.y = pointer.y - offset - panel_size.y;
+    }
+    let panel = Rect::from_min_size(min, panel_size);
+    painter.rect_filled(panel, 4.0, Color32::from_black_alpha(210));
+
+    let image = &frozen.screens[pixel.screen].1;
+    let grid = Rect::from_min_size(
+        egui::pos2(panel.center().x - side / 2.0, panel.min.y + 6.0),
+        egui::vec2(side, side),
+    );
+    for dy in -LOUPE_RADIUS..=LOUPE_RADIUS {
+        for dx in -LOUPE_RADIUS..=LOUPE_RADIUS {
+            let (x, y) = (pixel.x as i64 + dx, pixel.y as i64 + dy);
+            let color = u32::try_from(x)
+                .ok()
+                .zip(u32::try_from(y).ok())
+                .and_then(|(x, y)| image.get_pixel_checked(x, y))
+                .map_or(Color32::from_gray(40), |pixel| {
+                    let [r, g, b, a] = pixel.0;
+                    Color32::from_rgba_unmultiplied(r, g, b, a)
+                });
+            let cell = Rect::from_min_size(
+                grid.min
+                    + egui::vec2(
+                        (dx + LOUPE_RADIUS) as f32 * LOUPE_ZOOM,
+                        (dy + LOUPE_RADIUS) as f32 * LOUPE_ZOOM,
+                    ),
+                egui::vec2(LOUPE_ZOOM, LOUPE_ZOOM),
+            );
+            painter.rect_filled(cell, 0.0, color);
+            painter.rect_stroke(
+                cell,
+                0.0,
+                egui::Stroke::new(0.5, Color32::from_black_alpha(60)),
+            );
+        }
+    }
+    // The pixel under the pointer
+    let center = Rect::from_center_size(grid.center(), egui::vec2(LOUPE_ZOOM, LOUPE_ZOOM));
+    painter.rect_stroke(
+        center.expand(1.0),
+        0.0,
+        egui::Stroke::new(1.0, Color32::BLACK),
+    );
+    painter.rect_stroke(center, 0.0, egui::Stroke::new(1.0, Color32::WHITE));
+    painter.galley(egui::pos2(panel.min.x + 6.0, grid.max.y + 6.0), galley);
+}
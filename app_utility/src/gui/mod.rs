@@ -1,299 +1,881 @@
-mod actions;
-mod screenshots;
+mod area;
 mod shortcut;
-mod timer;
+mod view;
 
+use app_utility::core::{
+    actions::Action,
+    annotation::{
+        self, Annotations, ArrowHead, ArrowStyle, Dash, MarkerSequence, Numbering, Point,
+        Redaction, Shape, ShapeId,
+    },
+    capture::{Rectangle, Screenshots},
+    config::Config,
+    crop::{self, AspectRatio},
+    error::AppError,
+    export::{build_default_name, ExportFormat, PngCompression},
+    project::{PROJECT_EXTENSION, PROJECT_NAME},
+    scaling, selection,
+    session::{Effect, Modifier, Session},
+    shortcut::{AllShortcuts, KEY_NAMES},
+    svg::{SVG_EXTENSION, SVG_NAME},
+    text::{self, Alignment, FontFamily, TextStyle},
+    zoom::Zoom,
+};
 use arboard::{Clipboard, ImageData};
-use chrono::Local;
 use eframe::{
-    egui::{self, Color32, Key, Layout, Sense, TextureHandle, Visuals, Window},
+    egui::{self, Color32, Layout, Sense, TextureHandle, Visuals, Window},
     epaint::vec2,
     run_native, App, Frame,
 };
-use image::{self, load_from_memory, ImageError};
+use image::RgbaImage;
 use native_dialog::FileDialog;
 use std::{
     borrow::Cow,
-    fs,
-    time::{Duration, Instant},
+    path::{Path, PathBuf},
 };
 
 use self::{
-    actions::Action,
-    screenshots::Screenshots,
-    shortcut::{AllShortcuts, ShortCut},
-    timer::Timer,
+    area::AreaSelection,
+    view::{ImageView, RasterPreviews},
 };
 
+// Initial strength of the redactions, in pixels of the screenshot
+const DEFAULT_BLUR: f32 = 8.0;
+const DEFAULT_BLOCK_SIZE: u32 = 12;
+// How much a point of the mouse wheel magnifies the screenshot
+const WHEEL_ZOOM_SPEED: f32 = 0.005;
+
 struct AppUtility {
-    buffer: Option<Vec<u8>>,
-    default_name: String,
-    default_name_selected: bool,
-    default_number: usize,
-    default_path: String,
-    hide: bool,
-    modification: bool,
-    modifications_vector: Vec<Modifier>,
-    modified_element: ModifiedElement,
-    modifier: Modifier,
-    rectangle: Rectangle,
-    screenshots: Screenshots,
-    selecting_area: bool,
-    selection_mode: Selection,
-    shortcuts: AllShortcuts,
+    config_warning: Option<String>,
+    config_file: ConfigFile,
+    drawing: Drawing,
+    saved_config: Config,
+    session: Session,
     temp_shortcuts: AllShortcuts, // Temporary shortcuts for UI interaction
-    show_settings: bool,
     texture: Option<TextureHandle>,
-    timer: Timer,
-    view_image: bool,
-    show_error: bool,
+    // The original screenshot, shown while the crop area is chosen
+    crop_texture: Option<TextureHandle>,
+    previews: RasterPreviews,
+    error_report: Option<ErrorReport>,
+    // A project that would keep the pixels under the redactions, saved once the user confirms
+    unredacted_project: Option<PathBuf>,
+    // The file given on the command line, opened once the window is shown
+    open_on_start: Option<PathBuf>,
+    // Whether the window is over every screen, for selecting an area on any of them
+    covering_screens: bool,
+    // The frame in which the window was hidden to take a screenshot
+    hidden_at_frame: Option<u64>,
+    // The frozen screens the area is selected on
+    area_selection: Option<AreaSelection>,
 }
 
-struct Rectangle {
-    x: f32,
-    y: f32,
-    width: f32,
-    height: f32,
+// Whether the changed settings are written to the settings file
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ConfigFile {
+    Writable,
+    // It could not be loaded, and is only replaced once the user agrees
+    Unreadable,
+    // The user chose to keep it, the settings are not saved anymore
+    Kept,
 }
 
-enum Selection {
-    Fullscreen,
-    Area,
+// An action that failed, shown to the user until it is dismissed or retried
+struct ErrorReport {
+    error: AppError,
+    retry: Option<Retry>,
 }
 
-#[derive(PartialEq, Debug)]
-enum Modifier {
-    NotSelected,
-    Pen,
-    Rect,
-    Arrow,
-    Text,
-    Crop,
-    Line,
-    Circle,
+// What is performed again when the user asks to retry
+enum Retry {
+    Action(Action),
+    Effect(Effect),
+    SaveTo(PathBuf),
+    Open(PathBuf),
 }
 
-struct ModifiedElement {
+// State of the annotation that is being drawn. The sizes are in points of the window when
+// the screenshot fits it, so that they do not change with the zoom
+struct Drawing {
     stroke: egui::Stroke,
-    pen: Vec<Vec<(egui::Pos2, egui::Stroke)>>,
-    rect: Vec<Vec<(egui::Pos2, egui::Stroke)>>,
-    circle: Vec<Vec<(egui::Pos2, egui::Stroke)>>,
-    arrow: Vec<Vec<(egui::Pos2, egui::Stroke)>>,
-    line: Vec<Vec<(egui::Pos2, egui::Stroke)>>,
+    highlighter: egui::Stroke,
+    // Fill of the rectangles, circles and ellipses, and the radius of the corners of the
+    // rectangles
+    fill: Color32,
+    rounding: f32,
+    // Whether the rectangles and the ellipses are drawn as squares and circles
+    constrain: bool,
+    marker_radius: f32,
+    // How the lines and the arrows are drawn
+    dash: Dash,
+    arrow: ArrowStyle,
+    // Where the pointer went, in pixels of the screenshot
+    points: Vec<Point>,
     text: String,
-    entire_text: Vec<(egui::Pos2, String, egui::Stroke)>,
     text_modified: bool,
+    // The color of the text is the one of the stroke and its background the fill
+    text_style: TextStyle,
+    // The text annotation being edited again, and where to put the editor
+    editing: Option<ShapeId>,
+    text_pos: Option<egui::Pos2>,
+    // Area of the original screenshot that is kept by the crop, in its pixels
+    crop_area: Rectangle,
+    crop_aspect: AspectRatio,
+    crop_drag: Option<CropDrag>,
+    // The fill of a redaction is the color of the stroke
+    redaction: Redaction,
+    selected: Option<ShapeId>,
+    drag: Option<Drag>,
+}
+
+// The selected annotation being moved, or resized by one of its handles, in the pixels
+// of the screenshot. The annotation is changed only when the drag ends
+struct Drag {
+    id: ShapeId,
+    handle: Option<usize>,
+    from: Point,
+    to: Point,
+}
+
+// How the crop area follows the pointer, in the pixels of the original screenshot
+#[derive(Clone, Copy)]
+enum CropDrag {
+    Move { from: Point, area: Rectangle },
+    Corner { fixed: Point },
+}
+
+impl Drag {
+    fn preview(&self, shape: &Shape) -> Shape {
+        match self.handle {
+            Some(handle) => selection::resized(shape, handle, self.to),
+            None => {
+                let mut shape = shape.clone();
+                shape.translate(self.to - self.from);
+                shape
+            }
+        }
+    }
+}
+
+impl Drawing {
+    // The text being written, with the top left corner of its box at `pos`
+    fn text_shape(&self, pos: Point) -> Shape {
+        Shape::Text {
+            pos,
+            text: self.text.clone(),
+            style: TextStyle {
+                color: view::to_color(self.stroke.color),
+                background: view::to_color(self.fill),
+                ..self.text_style
+            },
+        }
+    }
+
+    // The annotation made by dragging the pointer over `points` with the current modifier
+    fn shape(&self, modifier: &Modifier, view: &ImageView) -> Option<Shape> {
+        // A marker goes where the pointer is released
+        if let (Modifier::Marker, Some(last)) = (modifier, self.points.last()) {
+            return Some(Shape::Marker {
+                center: *last,
+                radius: view.to_image_size(self.marker_radius),
+                color: view::to_color(self.stroke.color),
+            });
+        }
+        if self.points.len() < 2 {
+            return None;
+        }
+        let stroke = view.to_image_stroke(self.stroke);
+        let fill = view::to_color(self.fill);
+        let start = self.points[0];
+        let mut end = self.points[self.points.len() - 1];
+        if self.constrain && matches!(modifier, Modifier::Rect | Modifier::Ellipse) {
+            // A square or a circle, as big as the largest side of the drag
+            let side = (end.x - start.x).abs().max((end.y - start.y).abs());
+            end = start
+                + Point::new(
+                    side.copysign(end.x - start.x),
+                    side.copysign(end.y - start.y),
+                );
+        }
+        match modifier {
+            Modifier::Pen => Some(Shape::Pen {
+                points: self.points.clone(),
+                stroke,
+            }),
+            Modifier::Highlight => Some(Shape::Highlight {
+                points: self.points.clone(),
+                stroke: view.to_image_stroke(self.highlighter),
+            }),
+            Modifier::Line => Some(Shape::Line {
+                start,
+                end,
+                stroke,
+                dash: self.dash,
+            }),
+            Modifier::Arrow | Modifier::CurvedArrow => Some(Shape::Arrow {
+                start,
+                end,
+                // A curved arrow starts as an arc, its control point can then be dragged
+                control: (*modifier == Modifier::CurvedArrow).then(|| {
+                    let vec = end - start;
+                    start + vec * 0.5 + Point::new(vec.y, -vec.x) * 0.5
+                }),
+                stroke,
+                dash: self.dash,
+                style: ArrowStyle {
+                    size: view.to_image_size(self.arrow.size),
+                    ..self.arrow
+                },
+            }),
+            Modifier::Rect => Some(Shape::Rect {
+                start,
+                end,
+                stroke,
+                fill,
+                rounding: view.to_image_size(self.rounding),
+            }),
+            Modifier::Circle => Some(Shape::Circle {
+                center: start,
+                radius: start.distance(end),
+                stroke,
+                fill,
+            }),
+            Modifier::Ellipse => Some(Shape::Ellipse {
+                start,
+                end,
+                stroke,
+                fill,
+            }),
+            Modifier::Redact => Some(Shape::Redact {
+                start,
+                end,
+                redaction: match self.redaction {
+                    Redaction::Fill(_) => Redaction::Fill(stroke.color),
+                    redaction => redaction,
+                },
+            }),
+            Modifier::NotSelected
+            | Modifier::Text
+            | Modifier::Crop
+            | Modifier::Select
+            | Modifier::Marker => None,
+        }
+    }
 }
 
 impl AppUtility {
-    fn new(cc: &eframe::CreationContext<'_>) -> Self {
+    fn new(
+        cc: &eframe::CreationContext<'_>,
+        screenshots: Screenshots,
+        file: Option<PathBuf>,
+    ) -> Self {
         cc.egui_ctx.set_visuals(Visuals::light());
+        // The fonts of the text annotations, each one in a family of its own
+        let mut fonts = egui::FontDefinitions::default();
+        for (name, data) in text::fonts() {
+            fonts
+                .font_data
+                .insert(name.to_owned(), egui::FontData::from_static(data));
+            fonts
+                .families
+                .insert(egui::FontFamily::Name(name.into()), vec![name.to_owned()]);
+        }
+        cc.egui_ctx.set_fonts(fonts);
+        let mut session = Session::new(screenshots);
+        let mut config_warning = None;
+        let mut config_file = ConfigFile::Writable;
+
+        match Config::load() {
+            Ok(Some(config)) => session.apply_config(config),
+            Ok(None) => {}
+            Err(err) => {
+                config_file = ConfigFile::Unreadable;
+                config_warning = Some(format!(
+                "Warning: the settings could not be loaded, the default ones are used instead.\n{}",
+                err
+            ))
+            }
+        }
+
         Self {
-            buffer: None,
-            default_name: build_default_name(),
-            default_name_selected: true,
-            default_number: 0,
-            default_path: "screenshots".to_string(),
-            hide: false,
-            modification: false,
-            modifications_vector: Default::default(),
-            modifier: Modifier::NotSelected,
-            modified_element: ModifiedElement {
+            config_warning,
+            config_file,
+            drawing: Drawing {
                 stroke: egui::Stroke::new(1.0, egui::Color32::BLACK),
-                pen: Default::default(),
-                rect: Default::default(),
-                circle: Default::default(),
-                arrow: Default::default(),
-                line: Default::default(),
-                entire_text: Default::default(),
+                fill: Color32::TRANSPARENT,
+                rounding: 0.0,
+                constrain: false,
+                marker_radius: 14.0,
+                dash: Dash::Solid,
+                arrow: ArrowStyle::default(),
+                highlighter: egui::Stroke::new(
+                    14.0,
+                    view::to_color32(annotation::Color::HIGHLIGHTER_PALETTE[0].1),
+                ),
+                points: Vec::new(),
                 text: "Example".to_owned(),
                 text_modified: false,
+                text_style: TextStyle::default(),
+                editing: None,
+                text_pos: None,
+                crop_area: Rectangle::default(),
+                crop_aspect: AspectRatio::Free,
+                crop_drag: None,
+                redaction: Redaction::Blur(DEFAULT_BLUR),
+                selected: None,
+                drag: None,
             },
-            rectangle: Rectangle {
-                x: 0.0,
-                y: 0.0,
-                width: 0.0,
-                height: 0.0,
-            },
-            screenshots: Screenshots::new(),
-            selecting_area: false,
-            selection_mode: Selection::Fullscreen,
-            shortcuts: AllShortcuts::default(),
-            temp_shortcuts: AllShortcuts::default(), // Temporary shortcuts for UI interaction
-            show_settings: false,
+            saved_config: session.config(),
+            temp_shortcuts: session.shortcuts.clone(), // Temporary shortcuts for UI interaction
+            session,
             texture: None,
-            timer: Timer::new(),
-            view_image: false,
-            show_error: false,
+            crop_texture: None,
+            previews: RasterPreviews::default(),
+            error_report: None,
+            unredacted_project: None,
+            open_on_start: file,
+            covering_screens: false,
+            hidden_at_frame: None,
+            area_selection: None,
         }
     }
 
+    // Writes the settings file when something in the settings changed, once no text field
+    // is being edited anymore
+    fn persist_config(&mut self, ctx: &egui::Context) {
+        if self.config_file != ConfigFile::Writable || ctx.memory(|m| m.focus().is_some()) {
+            return;
+        }
+        let config = self.session.config();
+        if config == self.saved_config {
+            return;
+        }
+        if let Err(err) = config.save() {
+            self.config_warning = Some(format!(
+                "Warning: the settings could not be saved.\n{}",
+                err
+            ));
+        }
+        self.saved_config = config;
+    }
+
     fn make_action(&mut self, action: Action, ctx: &egui::Context, frame: &mut Frame) {
-        match action {
-            Action::Capture => {
-                self.hide = true;
-                self.timer.reset_timer();
-                frame.set_visible(false);
-            }
-            Action::Close => {
-                frame.close();
-            }
-            Action::Copy => {
-                let mut clipboard = Clipboard::new().unwrap();
-                let image = load_image_from_mem(&self.buffer.clone().unwrap()).unwrap();
-                let bytes = image.as_raw();
-                let image_data = ImageData {
-                    width: image.width() as usize,
-                    height: image.height() as usize,
-                    bytes: Cow::from(bytes.as_ref()),
-                };
-                clipboard.set_image(image_data).unwrap();
-            }
-            Action::HomePage => {
-                self.selecting_area = false;
-                self.view_image = false;
-                self.show_settings = false;
-            }
-            Action::ManageTimer => {
-                let now = Instant::now();
-                if now
-                    .duration_since(self.timer.start_instant.unwrap())
-                    .as_secs_f32()
-                    >= 1.0
-                {
-                    self.timer.decrement_timer();
-                    if self.timer.seconds == 0 {
-                        // self.timer.reset_timer();
-                        // self.hide = true;
-                        // frame.set_visible(false);
-                        self.make_action(Action::Capture, ctx, frame);
+        match self.session.dispatch(action) {
+            Ok(effects) => self.apply_effects(effects, ctx, frame),
+            Err(err) => self.report(err, Some(Retry::Action(action))),
+        }
+    }
+
+    fn report(&mut self, error: AppError, retry: Option<Retry>) {
+        log::error!("{}", error);
+        self.error_report = Some(ErrorReport { error, retry });
+    }
+
+    fn retry(&mut self, retry: Retry, ctx: &egui::Context, frame: &mut Frame) {
+        match retry {
+            Retry::Action(action) => self.make_action(action, ctx, frame),
+            Retry::Effect(effect) => self.apply_effects(vec![effect], ctx, frame),
+            Retry::SaveTo(path) => self.save_to(path),
+            Retry::Open(path) => self.open(path, ctx, frame),
+        }
+    }
+
+    // Stops at the first effect that fails, the following ones depend on it
+    fn apply_effects(&mut self, effects: Vec<Effect>, ctx: &egui::Context, frame: &mut Frame) {
+        for effect in effects {
+            let result = match &effect {
+                Effect::HideWindow => {
+                    frame.set_visible(false);
+                    self.hidden_at_frame = Some(ctx.frame_nr());
+                    Ok(())
+                }
+                Effect::ShowWindow => {
+                    frame.set_visible(true);
+                    Ok(())
+                }
+                Effect::DesktopFrozen => {
+                    self.area_selection = self
+                        .session
+                        .frozen
+                        .as_ref()
+                        .map(|frozen| AreaSelection::new(ctx, frozen));
+                    Ok(())
+                }
+                Effect::Close => {
+                    frame.close();
+                    Ok(())
+                }
+                Effect::CopyToClipboard => self.copy_to_clipboard(),
+                Effect::ChooseSavePath { location, filename } => {
+                    let res = match save_file_dialog(location, filename).show_save_single_file() {
+                        Ok(res) => Ok(res),
+                        Err(_) => save_file_dialog(Path::new("~"), filename)
+                            .show_save_single_file()
+                            .map_err(|err| AppError::Dialog(err.to_string())),
+                    };
+                    match res {
+                        Ok(Some(path)) => {
+                            self.confirm_save_to(path);
+                            Ok(())
+                        }
+                        Ok(None) => Ok(()),
+                        Err(err) => Err(err),
                     }
-                    self.timer.start_instant = Some(now);
                 }
-                ctx.request_repaint();
-            }
-            Action::Modify => {
-                self.modification = true;
-            }
-            Action::NewScreenshot => {
-                self.hide = false;
-                self.view_image = false;
-                self.selection_mode = Selection::Fullscreen;
-                self.selecting_area = false;
-                self.show_settings = false;
-                self.modified_element.pen.clear();
-                self.modified_element.rect.clear();
-                self.modified_element.entire_text.clear();
-                self.modified_element.arrow.clear();
-                self.modified_element.line.clear();
-                self.modified_element.circle.clear();
-                self.modifications_vector.clear();
-                self.modifier = Modifier::NotSelected;
-                self.modification = false;
-            }
-            Action::ResetTimer => {
-                self.timer.reset_timer();
-            }
-            Action::Save => {
-                let mut filename = build_default_name();
-                if !self.default_name_selected {
-                    if self.default_number != 0 {
-                        filename = format!("{}_{}", self.default_name, self.default_number);
-                        self.default_number += 1;
-                    } else {
-                        filename = self.default_name.clone();
-                        self.default_number += 1;
+                Effect::ChooseOpenPath { location } => {
+                    let extensions: Vec<&str> = ExportFormat::all()
+                        .iter()
+                        .flat_map(|format| format.extensions())
+                        .copied()
+                        .collect();
+                    match open_file_dialog(location, &extensions).show_open_single_file() {
+                        Ok(Some(path)) => {
+                            self.open(path, ctx, frame);
+                            Ok(())
+                        }
+                        Ok(None) => Ok(()),
+                        Err(err) => Err(AppError::Dialog(err.to_string())),
                     }
                 }
-                let mut dir = std::env::current_dir().unwrap();
-                dir.push(&self.default_path);
-                if !dir.exists() {
-                    dir = std::env::current_dir().unwrap();
-                    dir.push("screenshots");
+                Effect::ImageChanged => {
+                    if let Some(image) = self.session.image.as_ref() {
+                        self.texture = Some(load_texture(ctx, "new_image", image));
+                    }
+                    self.previews.clear();
+                    Ok(())
                 }
-                let res = match FileDialog::new()
-                    .set_location(&dir)
-                    .set_filename(&filename)
-                    .add_filter("PNG Image", &["png"])
-                    .add_filter("JPEG Image", &["jpg", "jpeg"])
-                    .add_filter("GIF Image", &["gif"])
-                    .show_save_single_file()
-                {
-                    Ok(res) => res,
-                    Err(_) => FileDialog::new()
-                        .set_location("~")
-                        .set_filename(&filename)
-                        .add_filter("PNG Image", &["png"])
-                        .add_filter("JPEG Image", &["jpg", "jpeg"])
-                        .add_filter("GIF Image", &["gif"])
-                        .show_save_single_file()
-                        .unwrap(),
-                };
-                match res {
-                    Some(res) => fs::write(res.clone(), self.buffer.clone().unwrap()).unwrap(),
-                    None => {}
+                Effect::Repaint => {
+                    ctx.request_repaint();
+                    Ok(())
                 }
+            };
+            if let Err(err) = result {
+                self.report(err, Some(Retry::Effect(effect)));
+                return;
             }
-            Action::SelectArea => {
-                if self.screenshots.default {
-                    self.selection_mode = Selection::Area;
-                    self.selecting_area = true;
+        }
+    }
+
+    // The crop starts from the current crop area, which can be adjusted
+    fn start_crop(&mut self) {
+        if let Some(original) = self.session.original.as_ref() {
+            self.drawing.crop_area = self.session.crop.unwrap_or(Rectangle {
+                x: 0.0,
+                y: 0.0,
+                width: original.width() as f32,
+                height: original.height() as f32,
+            });
+        }
+        self.drawing.crop_aspect = AspectRatio::Free;
+        self.drawing.crop_drag = None;
+    }
+
+    // Aspect ratio and size of the crop area
+    fn crop_ui(&mut self, ui: &mut egui::Ui) {
+        let Some((width, height)) = self
+            .session
+            .original
+            .as_ref()
+            .map(|original| original.dimensions())
+        else {
+            return;
+        };
+        let aspect = self.drawing.crop_aspect;
+        egui::ComboBox::from_id_source("crop_aspect")
+            .selected_text(aspect.name())
+            .width(60.0)
+            .show_ui(ui, |ui| {
+                for aspect in AspectRatio::all() {
+                    ui.selectable_value(&mut self.drawing.crop_aspect, aspect, aspect.name());
                 }
-            }
-            Action::SelectFullscreen => {
-                self.selection_mode = Selection::Fullscreen;
-                self.selecting_area = false;
-            }
-            Action::Settings => {
-                self.show_settings = true;
-            }
-            Action::SetTimer => {
-                self.timer.open_form();
-            }
-            Action::StartTimer => {
-                if self.timer.seconds > 0 {
-                    self.timer.start_timer();
-                } else {
-                    // self.make_action(Action::SetTimer, ctx, frame);
-                    self.make_action(Action::Capture, ctx, frame);
+            })
+            .response
+            .on_hover_text("Aspect ratio");
+        if self.drawing.crop_aspect != aspect {
+            self.drawing.crop_area = crop::fit(self.drawing.crop_area, self.drawing.crop_aspect);
+        }
+
+        let area = self.drawing.crop_area;
+        let mut area_width = area.width.round();
+        let mut area_height = area.height.round();
+        if ui
+            .add(
+                egui::DragValue::new(&mut area_width)
+                    .clamp_range(1.0..=width as f32)
+                    .fixed_decimals(0),
+            )
+            .on_hover_text("Width in pixels")
+            .changed()
+        {
+            self.drawing.crop_area =
+                crop::with_width(area, area_width, self.drawing.crop_aspect, width, height);
+        }
+        ui.label("x");
+        if ui
+            .add(
+                egui::DragValue::new(&mut area_height)
+                    .clamp_range(1.0..=height as f32)
+                    .fixed_decimals(0),
+            )
+            .on_hover_text("Height in pixels")
+            .changed()
+        {
+            self.drawing.crop_area =
+                crop::with_height(area, area_height, self.drawing.crop_aspect, width, height);
+        }
+        if ui
+            .button("  Reset  ")
+            .on_hover_text("Keep the whole screenshot")
+            .clicked()
+        {
+            self.drawing.crop_area = Rectangle {
+                x: 0.0,
+                y: 0.0,
+                width: width as f32,
+                height: height as f32,
+            };
+            self.drawing.crop_aspect = AspectRatio::Free;
+        }
+    }
+
+    // The text being written and how it looks
+    fn text_ui(&mut self, ui: &mut egui::Ui) {
+        egui::ScrollArea::vertical()
+            .min_scrolled_height(30.0)
+            .show(ui, |ui| {
+                ui.add(
+                    egui::TextEdit::multiline(&mut self.drawing.text)
+                        .desired_rows(1)
+                        .desired_width(100.0)
+                        .hint_text("Example"),
+                );
+            });
+        let style = &mut self.drawing.text_style;
+        egui::ComboBox::from_id_source("text_family")
+            .selected_text(style.family.name())
+            .width(60.0)
+            .show_ui(ui, |ui| {
+                for family in FontFamily::all() {
+                    ui.selectable_value(&mut style.family, family, family.name());
+                }
+            });
+        ui.add(
+            egui::DragValue::new(&mut style.size)
+                .clamp_range(4.0..=400.0)
+                .fixed_decimals(0)
+                .suffix(" px"),
+        )
+        .on_hover_text("Font size");
+        ui.toggle_value(&mut style.bold, egui::RichText::new(" B ").strong())
+            .on_hover_text("Bold");
+        ui.toggle_value(&mut style.italic, egui::RichText::new(" I ").italics())
+            .on_hover_text("Italic");
+        egui::ComboBox::from_id_source("text_alignment")
+            .selected_text(style.alignment.name())
+            .width(60.0)
+            .show_ui(ui, |ui| {
+                for alignment in Alignment::all() {
+                    ui.selectable_value(&mut style.alignment, alignment, alignment.name());
                 }
+            });
+        ui.add(
+            egui::DragValue::new(&mut style.padding)
+                .clamp_range(0.0..=100.0)
+                .fixed_decimals(0)
+                .suffix(" px"),
+        )
+        .on_hover_text("Padding of the background");
+    }
+
+    // Opens the text annotation again in the editor of the text tool
+    fn edit_text(&mut self, id: ShapeId, view: &ImageView) {
+        if let Some(Shape::Text { pos, text, style }) = self.session.annotations.get(id) {
+            self.drawing.text = text.clone();
+            self.drawing.text_style = *style;
+            self.drawing.stroke.color = view::to_color32(style.color);
+            self.drawing.fill = view::to_color32(style.background);
+            self.drawing.text_pos = Some(view.to_screen(*pos));
+            self.drawing.editing = Some(id);
+            self.session.modifier = Modifier::Text;
+        }
+    }
+
+    // The wheel zooms the screenshot around the pointer, dragging it with the middle button
+    // or with the space bar held moves it. True while moving it, when the tools ignore the
+    // pointer
+    fn zoom_and_pan(
+        &mut self,
+        ui: &egui::Ui,
+        response: &egui::Response,
+        frame: &Frame,
+        image_size: egui::Vec2,
+    ) -> bool {
+        let view = ImageView::new(frame, image_size, self.session.zoom);
+        let (image, viewer) = (Point::new(image_size.x, image_size.y), view.viewer_size());
+        if let Some(hover_pos) = response.hover_pos() {
+            let (scroll, pinch) = ui.input(|i| (i.scroll_delta.y, i.zoom_delta()));
+            let factor = (scroll * WHEEL_ZOOM_SPEED).exp() * pinch;
+            if factor != 1.0 {
+                self.session.zoom =
+                    self.session
+                        .zoom
+                        .zoomed_at(factor, view.to_viewer(hover_pos), image, viewer);
             }
-            Action::Undo => {
-                if let Some(last_modification) = self.modifications_vector.pop() {
-                    match last_modification {
-                        Modifier::NotSelected => {}
-                        Modifier::Pen => {
-                            self.modified_element
-                                .pen
-                                .remove(self.modified_element.pen.len() - 2);
-                        }
-                        Modifier::Line => {
-                            self.modified_element
-                                .line
-                                .remove(self.modified_element.line.len() - 2);
-                        }
-                        Modifier::Arrow => {
-                            self.modified_element
-                                .arrow
-                                .remove(self.modified_element.arrow.len() - 2);
-                        }
-                        Modifier::Rect => {
-                            self.modified_element
-                                .rect
-                                .remove(self.modified_element.rect.len() - 2);
-                        }
-                        Modifier::Circle => {
-                            self.modified_element
-                                .circle
-                                .remove(self.modified_element.circle.len() - 2);
+        }
+        // The space bar is for the text fields while they have the focus
+        let space = ui.input(|i| i.key_down(egui::Key::Space))
+            && ui.memory(|memory| memory.focus().is_none());
+        let dragging = response.dragged_by(egui::PointerButton::Middle)
+            || (space && response.dragged_by(egui::PointerButton::Primary));
+        if dragging {
+            let delta = response.drag_delta();
+            self.session.zoom =
+                self.session
+                    .zoom
+                    .panned(Point::new(delta.x, delta.y), image, viewer);
+            ui.ctx().set_cursor_icon(egui::CursorIcon::Grabbing);
+        } else if space && response.hovered() {
+            ui.ctx().set_cursor_icon(egui::CursorIcon::Grab);
+        }
+        dragging || space
+    }
+
+    // The zoom of the viewer, with the buttons to fit the screenshot in it or to show it at
+    // its actual size
+    fn zoom_ui(&mut self, ctx: &egui::Context, view: &ImageView) {
+        let image = view.to_image(view.rect.max);
+        let viewer = view.viewer_size();
+        let scale = self.session.zoom.scale(image, viewer);
+        egui::Area::new("zoom")
+            .fixed_pos(view.viewer.right_bottom() - vec2(10.0, 10.0))
+            .pivot(egui::Align2::RIGHT_BOTTOM)
+            .order(egui::layers::Order::Foreground)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        // The screenshot has the pixels of the screen, not its points
+                        ui.label(format!(
+                            "{:.0}%",
+                            scaling::to_pixels(scale, ctx.pixels_per_point()) * 100.0
+                        ))
+                        .on_hover_text(
+                            "Scroll to zoom, drag with the middle button or hold space to move",
+                        );
+                        if ui
+                            .add_enabled(self.session.zoom != Zoom::Fit, egui::Button::new("Fit"))
+                            .on_hover_text("Fit the screenshot in the window")
+                            .clicked()
+                        {
+                            self.session.zoom = Zoom::Fit;
                         }
-                        Modifier::Text => {
-                            self.modified_element.entire_text.pop();
+                        if ui
+                            .button("100%")
+                            .on_hover_text("Show the screenshot at its actual size")
+                            .clicked()
+                        {
+                            self.session.zoom = self.session.zoom.scaled_at(
+                                scaling::to_points(1.0, ctx.pixels_per_point()),
+                                viewer * 0.5,
+                                image,
+                                viewer,
+                            );
                         }
-                        Modifier::Crop => {}
+                    });
+                });
+            });
+    }
+
+    // Fill of the closed shapes and, for the rectangles, the radius of their corners
+    fn fill_ui(&mut self, ui: &mut egui::Ui, rounded: bool) {
+        ui.label("Fill");
+        egui::color_picker::color_edit_button_srgba(
+            ui,
+            &mut self.drawing.fill,
+            egui::color_picker::Alpha::OnlyBlend,
+        )
+        .on_hover_text("Fill color, transparent for no fill");
+        if rounded {
+            ui.add(
+                egui::DragValue::new(&mut self.drawing.rounding)
+                    .clamp_range(0.0..=100.0)
+                    .fixed_decimals(0),
+            )
+            .on_hover_text("Radius of the corners");
+        }
+    }
+
+    // Dashes of the lines and the arrows, and the heads of the arrows
+    fn line_ui(&mut self, ui: &mut egui::Ui, arrow: bool) {
+        let dash = &mut self.drawing.dash;
+        egui::ComboBox::from_id_source("dash")
+            .selected_text(dash.name())
+            .width(70.0)
+            .show_ui(ui, |ui| {
+                for kind in Dash::all() {
+                    ui.selectable_value(dash, kind, kind.name());
+                }
+            });
+        if arrow {
+            let style = &mut self.drawing.arrow;
+            egui::ComboBox::from_id_source("arrow_head")
+                .selected_text(style.head.name())
+                .width(70.0)
+                .show_ui(ui, |ui| {
+                    for head in ArrowHead::all() {
+                        ui.selectable_value(&mut style.head, head, head.name());
+                    }
+                })
+                .response
+                .on_hover_text("Head of the arrow");
+            if style.head != ArrowHead::None {
+                ui.checkbox(&mut style.both_ends, "Both ends");
+                ui.add(
+                    egui::DragValue::new(&mut style.size)
+                        .clamp_range(4.0..=80.0)
+                        .fixed_decimals(0),
+                )
+                .on_hover_text("Size of the heads");
+            }
+        }
+    }
+
+    // Color of the palette and width of the highlighter
+    fn highlighter_ui(&mut self, ui: &mut egui::Ui) {
+        for (name, color) in annotation::Color::HIGHLIGHTER_PALETTE {
+            let color = view::to_color32(color);
+            let selected = self.drawing.highlighter.color == color;
+            if ui
+                .add(egui::Button::new("    ").fill(color).stroke(if selected {
+                    egui::Stroke::new(2.0, Color32::DARK_GRAY)
+                } else {
+                    egui::Stroke::NONE
+                }))
+                .on_hover_text(name)
+                .clicked()
+            {
+                self.drawing.highlighter.color = color;
+            }
+        }
+        ui.add(
+            egui::Slider::new(&mut self.drawing.highlighter.width, 4.0..=40.0).fixed_decimals(0),
+        )
+        .on_hover_text("Width of the highlighter");
+    }
+
+    // How the markers are numbered and how big they are
+    fn marker_ui(&mut self, ui: &mut egui::Ui) {
+        let mut numbering = self.session.annotations.numbering;
+        egui::ComboBox::from_id_source("marker_sequence")
+            .selected_text(match numbering.sequence {
+                MarkerSequence::Numbers => "1, 2, 3",
+                MarkerSequence::Letters => "A, B, C",
+            })
+            .width(70.0)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut numbering.sequence, MarkerSequence::Numbers, "1, 2, 3");
+                ui.selectable_value(&mut numbering.sequence, MarkerSequence::Letters, "A, B, C");
+            });
+        // The letters start at A, there is none for 0
+        let sequence = numbering.sequence;
+        numbering.start = numbering.start.max(sequence.first());
+        ui.label("from");
+        ui.add(
+            egui::DragValue::new(&mut numbering.start)
+                .clamp_range(sequence.first()..=999)
+                .custom_formatter(move |value, _| {
+                    Numbering {
+                        sequence,
+                        start: value as u32,
+                    }
+                    .label(0)
+                }),
+        )
+        .on_hover_text("Label of the first marker");
+        self.session.renumber(numbering);
+        ui.add(
+            egui::DragValue::new(&mut self.drawing.marker_radius)
+                .clamp_range(6.0..=60.0)
+                .fixed_decimals(0),
+        )
+        .on_hover_text("Size of the markers");
+    }
+
+    // How the redactions hide the pixels, the fill uses the color of the stroke
+    fn redaction_ui(&mut self, ui: &mut egui::Ui) {
+        let redaction = &mut self.drawing.redaction;
+        egui::ComboBox::from_id_source("redaction")
+            .selected_text(redaction.name())
+            .width(70.0)
+            .show_ui(ui, |ui| {
+                for kind in [
+                    Redaction::Blur(DEFAULT_BLUR),
+                    Redaction::Pixelate(DEFAULT_BLOCK_SIZE),
+                    Redaction::Fill(view::to_color(self.drawing.stroke.color)),
+                ] {
+                    if ui
+                        .selectable_label(redaction.name() == kind.name(), kind.name())
+                        .clicked()
+                        && redaction.name() != kind.name()
+                    {
+                        *redaction = kind;
                     }
                 }
+            });
+        match redaction {
+            Redaction::Blur(sigma) => {
+                ui.add(egui::Slider::new(sigma, 1.0..=30.0).fixed_decimals(0))
+                    .on_hover_text("Strength of the blur");
+            }
+            Redaction::Pixelate(block_size) => {
+                ui.add(
+                    egui::DragValue::new(block_size)
+                        .clamp_range(2..=100)
+                        .suffix(" px"),
+                )
+                .on_hover_text("Size of the blocks");
             }
+            Redaction::Fill(_) => {}
+        }
+    }
+
+    fn copy_to_clipboard(&self) -> Result<(), AppError> {
+        let image = self
+            .session
+            .rendered_image()
+            .ok_or(AppError::NoScreenshot)?;
+        let mut clipboard = Clipboard::new().map_err(|err| AppError::Clipboard(err.to_string()))?;
+        let image_data = ImageData {
+            width: image.width() as usize,
+            height: image.height() as usize,
+            bytes: Cow::from(image.into_raw()),
+        };
+        clipboard
+            .set_image(image_data)
+            .map_err(|err| AppError::Clipboard(err.to_string()))
+    }
+
+    // The user is asked first when the redacted pixels would be saved
+    fn confirm_save_to(&mut self, path: PathBuf) {
+        if self.session.reveals_redactions(&path) {
+            self.unredacted_project = Some(path);
+        } else {
+            self.save_to(path);
+        }
+    }
+
+    fn save_to(&mut self, path: PathBuf) {
+        if let Err(err) = self.session.save_to(&path) {
+            self.report(err, Some(Retry::SaveTo(path)));
+        }
+    }
+
+    fn open(&mut self, path: PathBuf, ctx: &egui::Context, frame: &mut Frame) {
+        match self.session.open(&path) {
+            Ok(effects) => {
+                self.drawing.selected = None;
+                self.drawing.drag = None;
+                self.apply_effects(effects, ctx, frame);
+            }
+            Err(err) => self.report(err, Some(Retry::Open(path))),
         }
     }
 }
@@ -305,47 +887,63 @@ impl App for AppUtility {
         let pos_central_y = 30.0;
         let window_default_color = Color32::LIGHT_BLUE;
 
-        if self.hide {
-            // println!("Now I'm hiding");
-            std::thread::sleep(Duration::from_millis(300));
-            let screen = self.screenshots.get_screen();
-            let img;
-            match self.selection_mode {
-                Selection::Area => {
-                    img = screen
-                        .capture_area(
-                            self.rectangle.x.floor() as i32,
-                            self.rectangle.y.floor() as i32,
-                            self.rectangle.width.floor() as u32,
-                            self.rectangle.height.floor() as u32,
-                        )
-                        .unwrap();
-                    // println!("Capturing area screen!");
-                }
-                Selection::Fullscreen => {
-                    img = screen.capture().unwrap();
-                    // println!("Capturing screen!");
+        if let Some(path) = self.open_on_start.take() {
+            self.open(path, ctx, frame);
+        }
+
+        // An edit of the style of an annotation, e.g. dragging its width, ends with it
+        if ctx.input(|i| i.pointer.any_released()) {
+            self.session.history.end_edit();
+        }
+
+        // A file dropped on the window is opened, unless a screenshot is being taken
+        if !self.session.hide && !self.session.selecting_area {
+            let dropped = ctx.input(|i| {
+                i.raw
+                    .dropped_files
+                    .iter()
+                    .find_map(|file| file.path.clone())
+            });
+            if let Some(path) = dropped {
+                self.open(path, ctx, frame);
+            }
+            if ctx.input(|i| !i.raw.hovered_files.is_empty()) {
+                let screen = ctx.screen_rect();
+                let painter = ctx.layer_painter(egui::LayerId::new(
+                    egui::Order::Foreground,
+                    egui::Id::new("drop_files"),
+                ));
+                painter.rect_filled(
+                    screen,
+                    egui::Rounding::none(),
+                    Color32::from_black_alpha(120),
+                );
+                painter.text(
+                    screen.center(),
+                    egui::Align2::CENTER_CENTER,
+                    "Drop to open",
+                    egui::FontId::proportional(32.0),
+                    Color32::WHITE,
+                );
+            }
+        }
+
+        // The screens are captured once a whole frame went by with the window hidden, so
+        // that it is not on them, without blocking the window in the meantime
+        let hiding = self
+            .hidden_at_frame
+            .is_some_and(|hidden_at| ctx.frame_nr() <= hidden_at + 1);
+        if self.session.hide && hiding {
+            ctx.request_repaint();
+        } else if self.session.hide {
+            self.hidden_at_frame = None;
+            match self.session.capture() {
+                Ok(effects) => self.apply_effects(effects, ctx, frame),
+                Err(err) => {
+                    frame.set_visible(true);
+                    self.report(err, Some(Retry::Action(Action::Capture)));
                 }
             }
-            self.buffer = Some(img.to_png(None).unwrap());
-            self.texture = Some(ctx.load_texture(
-                "new_image",
-                load_image_from_mem(&self.buffer.clone().unwrap()).unwrap(),
-                Default::default(),
-            ));
-            self.hide = false;
-            self.view_image = true;
-            self.selecting_area = false;
-            self.modification = false;
-            self.show_settings = false;
-            self.modified_element.pen.clear();
-            self.modified_element.rect.clear();
-            self.modified_element.entire_text.clear();
-            self.modified_element.arrow.clear();
-            self.modified_element.line.clear();
-            self.modified_element.circle.clear();
-            self.modifications_vector.clear();
-            frame.set_visible(true);
         }
 
         Window::new("home_page menu_bar")
@@ -364,11 +962,11 @@ impl App for AppUtility {
             .anchor(egui::Align2::CENTER_TOP, [0.0, 15.0])
             .resizable(false)
             .open(
-                &mut (!self.view_image
-                    && !self.selecting_area
-                    && !self.show_settings
-                    && !self.timer.form_opened()
-                    && !self.timer.is_running()),
+                &mut (!self.session.view_image
+                    && !self.session.selecting_area
+                    && !self.session.show_settings
+                    && !self.session.timer.form_opened()
+                    && !self.session.timer.is_running()),
             )
             .show(ctx, |ui| {
                 ui.with_layout(
@@ -381,16 +979,16 @@ impl App for AppUtility {
                         cross_justify: true,
                     },
                     |ui| {
-                        match self.shortcuts.listener(ctx, self.view_image, self.selecting_area) {
-                            Some(action) => self.make_action(action, ctx, frame),
-                            None => {}
+                        if let Some(action) = shortcut::listener(
+                            &self.session.shortcuts,
+                            ctx,
+                            self.session.view_image,
+                            self.session.selecting_area,
+                        ) {
+                            self.make_action(action, ctx, frame);
                         }
 
-                        if !self.view_image {
-                            // if self.timer.is_running() {
-                            //     self.make_action(Action::ManageTimer, ctx, frame);
-                            // }
-
+                        if !self.session.view_image {
                             if custom_button(
                                 ui,
                                 "📷  Fullscreen shot",
@@ -401,25 +999,40 @@ impl App for AppUtility {
                             .clicked()
                             {
                                 self.make_action(Action::SelectFullscreen, ctx, frame);
-                                // println!("Capture clicked");
                                 self.make_action(Action::StartTimer, ctx, frame);
                             }
 
-                            ui.add_space(10.0);
-                            ui.add_enabled_ui(self.screenshots.default, |ui| {
+                            if self.session.screenshots.total_screens() > 1 {
+                                ui.add_space(10.0);
                                 if custom_button(
                                     ui,
-                                    "⛶  Area shot",
+                                    "🖵  All screens shot",
                                     egui::Color32::WHITE,
-                                    egui::Color32::from_rgb(142, 167, 233),
+                                    egui::Color32::from_rgb(101, 120, 196),
                                 )
-                                .on_hover_text("Take a screenshot of an area")
+                                .on_hover_text("Take a single screenshot of every screen")
                                 .clicked()
                                 {
-                                    self.make_action(Action::SelectArea, ctx, frame);
-                                    // println!("You want an area shot?");
+                                    self.make_action(Action::SelectAllScreens, ctx, frame);
+                                    self.make_action(Action::StartTimer, ctx, frame);
                                 }
-                            });
+                            }
+
+                            ui.add_space(10.0);
+                            if custom_button(
+                                ui,
+                                "⛶  Area shot",
+                                egui::Color32::WHITE,
+                                egui::Color32::from_rgb(142, 167, 233),
+                            )
+                            .on_hover_text(
+                                "Freeze the screens and select an area of them, on any screen",
+                            )
+                            .clicked()
+                            {
+                                self.make_action(Action::SelectArea, ctx, frame);
+                                self.make_action(Action::StartTimer, ctx, frame);
+                            }
 
                             ui.add_space(10.0);
                             if custom_button(
@@ -433,7 +1046,22 @@ impl App for AppUtility {
                             {
                                 self.make_action(Action::SetTimer, ctx, frame);
                             }
-                            ui.label(format!("Actual delay: {}", self.timer.seconds));
+                            ui.label(format!("Actual delay: {}", self.session.timer.seconds));
+
+                            ui.add_space(10.0);
+                            if custom_button(
+                                ui,
+                                "📂  Open",
+                                egui::Color32::DARK_GRAY,
+                                egui::Color32::from_rgb(204, 229, 255),
+                            )
+                            .on_hover_text(
+                                "Open an image or a saved project, or drop it on the window",
+                            )
+                            .clicked()
+                            {
+                                self.make_action(Action::Open, ctx, frame);
+                            }
 
                             ui.add_space(10.0);
                             if custom_button(
@@ -466,10 +1094,9 @@ impl App for AppUtility {
             });
 
         Window::new("screenshot_taken toolbar")
-            //TODO: QUI BISOGNA INSERIRE I BOTTONI DI MODIFICA, DI COPIA ECC...
             .title_bar(false)
             .anchor(egui::Align2::CENTER_TOP, [0.0, 15.0])
-            .open(&mut (self.view_image.clone() && !self.show_settings))
+            .open(&mut (self.session.view_image && !self.session.show_settings))
             .frame(egui::Frame {
                 fill: window_default_color,
                 stroke: egui::Stroke::new(0.5, egui::Color32::GRAY),
@@ -493,13 +1120,16 @@ impl App for AppUtility {
                         cross_justify: true,
                     },
                     |ui| {
-                        match self.shortcuts.listener(ctx, self.view_image, self.selecting_area) {
-                            Some(action) => self.make_action(action, ctx, frame),
-                            None => {}
+                        if let Some(action) = shortcut::listener(
+                            &self.session.shortcuts,
+                            ctx,
+                            self.session.view_image,
+                            self.session.selecting_area,
+                        ) {
+                            self.make_action(action, ctx, frame);
                         }
 
-                        if self.view_image && !self.modification {
-                            // println!("Now I'm seeing the image");
+                        if self.session.view_image && !self.session.modification {
                             if custom_button(
                                 ui,
                                 "  Modify  ",
@@ -546,7 +1176,6 @@ impl App for AppUtility {
                             .clicked()
                             {
                                 self.make_action(Action::NewScreenshot, ctx, frame);
-                                //Magari se ci sono delle modifiche non salvate conviene chiedere conferma (Discard all unsaved changes?)
                             }
 
                             if custom_button(
@@ -573,111 +1202,289 @@ impl App for AppUtility {
                                 self.make_action(Action::Close, ctx, frame);
                             }
                         } else {
-                            ui.selectable_value(&mut self.modifier, Modifier::Pen, " 🖊  ")
-                                .on_hover_text("Draw");
-                            ui.selectable_value(&mut self.modifier, Modifier::Line, "  /  ")
-                                .on_hover_text("Draw a line");
-                            ui.selectable_value(&mut self.modifier, Modifier::Arrow, "  ↖  ")
-                                .on_hover_text("Draw an arrow");
-                            ui.selectable_value(&mut self.modifier, Modifier::Rect, "  ☐  ")
-                                .on_hover_text("Draw a rectangle");
-                            ui.selectable_value(&mut self.modifier, Modifier::Circle, "  ⭕  ")
-                                .on_hover_text("Draw a circle");
+                            ui.selectable_value(
+                                &mut self.session.modifier,
+                                Modifier::Select,
+                                "  ☝  ",
+                            )
+                            .on_hover_text("Select, move and resize an annotation");
                             ui.label("|");
-                            ui.selectable_value(&mut self.modifier, Modifier::Text, "  T  ")
-                                .on_hover_text("Write a text");
-
-                            if self.modifier == Modifier::Text {
-                                egui::ScrollArea::vertical().min_scrolled_height(30.0).show(
-                                    ui,
-                                    |ui| {
-                                        ui.add(
-                                            egui::TextEdit::multiline(
-                                                &mut self.modified_element.text,
-                                            )
-                                            .desired_rows(1)
-                                            .desired_width(100.0)
-                                            .hint_text("Example"),
+                            ui.selectable_value(&mut self.session.modifier, Modifier::Pen, " 🖊  ")
+                                .on_hover_text("Draw");
+                            ui.selectable_value(
+                                &mut self.session.modifier,
+                                Modifier::Highlight,
+                                "  ✏  ",
+                            )
+                            .on_hover_text("Highlight");
+                            if self.session.modifier == Modifier::Highlight {
+                                self.highlighter_ui(ui);
+                            }
+                            ui.selectable_value(
+                                &mut self.session.modifier,
+                                Modifier::Line,
+                                "  /  ",
+                            )
+                            .on_hover_text("Draw a line");
+                            ui.selectable_value(
+                                &mut self.session.modifier,
+                                Modifier::Arrow,
+                                "  ↖  ",
+                            )
+                            .on_hover_text("Draw an arrow");
+                            ui.selectable_value(
+                                &mut self.session.modifier,
+                                Modifier::CurvedArrow,
+                                "  ⤴  ",
+                            )
+                            .on_hover_text(
+                                "Draw a curved arrow, drag its middle handle to bend it",
+                            );
+                            let selected_line = self.drawing.selected.and_then(|id| {
+                                match self.session.annotations.get(id) {
+                                    Some(Shape::Line { .. }) => Some((id, false)),
+                                    Some(Shape::Arrow { .. }) => Some((id, true)),
+                                    _ => None,
+                                }
+                            });
+                            let drawn_line = match self.session.modifier {
+                                Modifier::Line => Some(false),
+                                Modifier::Arrow | Modifier::CurvedArrow => Some(true),
+                                _ => None,
+                            };
+                            if let Some(arrow) =
+                                drawn_line.or(selected_line.map(|(_, arrow)| arrow))
+                            {
+                                let (dash, style) = (self.drawing.dash, self.drawing.arrow);
+                                self.line_ui(ui, arrow);
+                                if let (Some((id, _)), Some(texture)) =
+                                    (selected_line, &self.texture)
+                                {
+                                    if (self.drawing.dash, self.drawing.arrow) != (dash, style) {
+                                        let view = ImageView::new(
+                                            frame,
+                                            texture.size_vec2(),
+                                            self.session.zoom,
                                         );
-                                    },
-                                );
+                                        self.session.change_line_style(
+                                            id,
+                                            self.drawing.dash,
+                                            ArrowStyle {
+                                                size: view.to_image_size(self.drawing.arrow.size),
+                                                ..self.drawing.arrow
+                                            },
+                                        );
+                                    }
+                                }
+                            }
+                            ui.selectable_value(
+                                &mut self.session.modifier,
+                                Modifier::Rect,
+                                "  ☐  ",
+                            )
+                            .on_hover_text("Draw a rectangle");
+                            ui.selectable_value(
+                                &mut self.session.modifier,
+                                Modifier::Circle,
+                                "  ⭕  ",
+                            )
+                            .on_hover_text("Draw a circle");
+                            ui.selectable_value(
+                                &mut self.session.modifier,
+                                Modifier::Ellipse,
+                                "  ⚪  ",
+                            )
+                            .on_hover_text("Draw an ellipse, hold shift for a circle");
+                            ui.selectable_value(
+                                &mut self.session.modifier,
+                                Modifier::Marker,
+                                "  🔢  ",
+                            )
+                            .on_hover_text("Place numbered steps");
+                            if self.session.modifier == Modifier::Marker {
+                                self.marker_ui(ui);
+                            }
+                            ui.selectable_value(
+                                &mut self.session.modifier,
+                                Modifier::Redact,
+                                "  🔳  ",
+                            )
+                            .on_hover_text("Blur, pixelate or fill an area");
+                            let selected_redaction = self.drawing.selected.filter(|id| {
+                                matches!(
+                                    self.session.annotations.get(*id),
+                                    Some(Shape::Redact { .. })
+                                )
+                            });
+                            if self.session.modifier == Modifier::Redact
+                                || selected_redaction.is_some()
+                            {
+                                let redaction = self.drawing.redaction;
+                                self.redaction_ui(ui);
+                                if let Some(id) = selected_redaction {
+                                    if self.drawing.redaction != redaction {
+                                        let fill = view::to_color(self.drawing.stroke.color);
+                                        self.session.change_redaction(
+                                            id,
+                                            match self.drawing.redaction {
+                                                Redaction::Fill(_) => Redaction::Fill(fill),
+                                                redaction => redaction,
+                                            },
+                                        );
+                                    }
+                                }
+                            }
+                            ui.label("|");
+                            ui.selectable_value(
+                                &mut self.session.modifier,
+                                Modifier::Text,
+                                "  T  ",
+                            )
+                            .on_hover_text("Write a text");
 
+                            if self.session.modifier == Modifier::Text {
+                                self.text_ui(ui);
                                 if ui.button("  Save text ").clicked() {
-                                    self.modified_element.text_modified = true;
-                                    self.modifications_vector.push(Modifier::Text);
+                                    self.drawing.text_modified = true;
                                 };
                                 if ui.button("  X  ").on_hover_text("Close text").clicked() {
-                                    self.modifier = Modifier::NotSelected;
+                                    self.session.modifier = Modifier::NotSelected;
                                 };
                             }
 
                             ui.label("|");
-                            ui.selectable_value(&mut self.modifier, Modifier::Crop, "  ⛶  ")
-                                .on_hover_text(" Crop area ");
+                            if ui
+                                .selectable_value(
+                                    &mut self.session.modifier,
+                                    Modifier::Crop,
+                                    "  ⛶  ",
+                                )
+                                .on_hover_text(" Crop area ")
+                                .clicked()
+                            {
+                                self.start_crop();
+                            }
 
-                            if self.modifier == Modifier::Crop {
+                            if self.session.modifier == Modifier::Crop {
+                                self.crop_ui(ui);
                                 if ui.button("  Save Crop ").clicked() {
-                                    self.modifier = Modifier::NotSelected;
-                                    self.selection_mode = Selection::Area;
-                                    // println!("I've cropped");
-                                    self.hide = true;
+                                    self.session.modifier = Modifier::NotSelected;
+                                    let effects = self.session.crop(self.drawing.crop_area);
+                                    self.apply_effects(effects, ctx, frame);
                                 }
                                 if ui.button("  X  ").on_hover_text("Close crop").clicked() {
-                                    self.modifier = Modifier::NotSelected;
+                                    self.session.modifier = Modifier::NotSelected;
                                 }
                             }
                             ui.label("|");
-                            egui::stroke_ui(ui, &mut self.modified_element.stroke, "Stroke");
+                            let stroke = self.drawing.stroke;
+                            egui::stroke_ui(ui, &mut self.drawing.stroke, "Stroke");
+                            if let (Some(id), Some(texture)) =
+                                (self.drawing.selected, &self.texture)
+                            {
+                                // The stroke of the selected annotation is the one being edited
+                                if self.drawing.stroke != stroke {
+                                    let view = ImageView::new(
+                                        frame,
+                                        texture.size_vec2(),
+                                        self.session.zoom,
+                                    );
+                                    self.session.restyle_annotation(
+                                        id,
+                                        view.to_image_stroke(self.drawing.stroke),
+                                    );
+                                }
+                            }
+                            let selected = self
+                                .drawing
+                                .selected
+                                .and_then(|id| self.session.annotations.get(id));
+                            if matches!(
+                                self.session.modifier,
+                                Modifier::Rect
+                                    | Modifier::Circle
+                                    | Modifier::Ellipse
+                                    | Modifier::Text
+                            ) || selected.and_then(Shape::fill).is_some()
+                            {
+                                let rounded = self.session.modifier == Modifier::Rect
+                                    || matches!(selected, Some(Shape::Rect { .. }));
+                                let (fill, rounding) = (self.drawing.fill, self.drawing.rounding);
+                                self.fill_ui(ui, rounded);
+                                if let (Some(id), Some(texture)) =
+                                    (self.drawing.selected, &self.texture)
+                                {
+                                    if (self.drawing.fill, self.drawing.rounding)
+                                        != (fill, rounding)
+                                    {
+                                        let view = ImageView::new(
+                                            frame,
+                                            texture.size_vec2(),
+                                            self.session.zoom,
+                                        );
+                                        self.session.refill_annotation(
+                                            id,
+                                            view::to_color(self.drawing.fill),
+                                            view.to_image_size(self.drawing.rounding),
+                                        );
+                                    }
+                                }
+                            }
+                            if let Some(id) = self.drawing.selected {
+                                if ui
+                                    .button("  🗑  ")
+                                    .on_hover_text("Delete the selected annotation")
+                                    .clicked()
+                                {
+                                    self.session.delete_annotation(id);
+                                    self.drawing.selected = None;
+                                }
+                            }
                             ui.label("|");
 
-                            if ui.button("  ⟲  ").on_hover_text("undo").clicked() {
+                            if ui
+                                .add_enabled(
+                                    self.session.history.can_undo(),
+                                    egui::Button::new("  ⟲  "),
+                                )
+                                .on_hover_text("undo")
+                                .clicked()
+                            {
                                 self.make_action(Action::Undo, ctx, frame);
                             }
+                            if ui
+                                .add_enabled(
+                                    self.session.history.can_redo(),
+                                    egui::Button::new("  ⟳  "),
+                                )
+                                .on_hover_text("redo")
+                                .clicked()
+                            {
+                                self.make_action(Action::Redo, ctx, frame);
+                            }
                             if ui
                                 .button("  Cancel  ")
                                 .on_hover_text("undo all modifications")
                                 .clicked()
                             {
-                                self.modified_element.pen.clear();
-                                self.modified_element.rect.clear();
-                                self.modified_element.entire_text.clear();
-                                self.modified_element.arrow.clear();
-                                self.modified_element.line.clear();
-                                self.modified_element.circle.clear();
-                                self.modifications_vector.clear();
-                                self.modifier = Modifier::NotSelected;
+                                let effects = self.session.undo_all();
+                                self.apply_effects(effects, ctx, frame);
+                                self.session.modifier = Modifier::NotSelected;
                             }
                             if ui.button("  Save  ").clicked() {
-                                let dim_img = resize_to_fit_container(
-                                    frame.info().window_info.size.x / 3.0 * 2.0,
-                                    frame.info().window_info.size.y / 3.0 * 2.0,
-                                    self.texture.clone().unwrap().size_vec2()[0],
-                                    self.texture.clone().unwrap().size_vec2()[1],
-                                );
-
-                                let mut adj = 1.0;
-                                if cfg!(target_os = "windows") {
-                                    adj = frame.info().native_pixels_per_point.unwrap();
-                                }
-
-                                self.rectangle = Rectangle {
-                                    x: ((frame.info().window_info.size[0] - dim_img.0) / 2.0) * adj,
-                                    y: ((frame.info().window_info.size[1] - dim_img.1) / 2.0) * adj,
-                                    width: dim_img.0 * adj,
-                                    height: dim_img.1 * adj,
-                                };
-                                self.modifier = Modifier::NotSelected;
-                                self.selection_mode = Selection::Area;
-                                self.hide = true;
+                                self.session.modifier = Modifier::NotSelected;
+                                self.session.modification = false;
                             }
                             if ui.button("  X  ").on_hover_text("Close").clicked() {
-                                self.modification = false;
+                                self.session.modification = false;
                             }
                         }
                     },
                 )
             });
 
+        // The session is modified while drawing, so the window works on a copy of the flag
+        let mut view_image = self.session.view_image;
         Window::new("screenshot_view")
             .title_bar(false)
             .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
@@ -688,298 +1495,397 @@ impl App for AppUtility {
             })
             .fixed_size([1200.0, 600.0])
             .resizable(false)
-            .open(&mut self.view_image)
+            .open(&mut view_image)
             .show(ctx, |ui| {
-                let dim_img = resize_to_fit_container(
-                    frame.info().window_info.size.x / 3.0 * 2.0,
-                    frame.info().window_info.size.y / 3.0 * 2.0,
-                    self.texture.clone().unwrap().size_vec2()[0],
-                    self.texture.clone().unwrap().size_vec2()[1],
-                );
-                let (mut response, painter) =
-                    ui.allocate_painter(vec2(dim_img.0, dim_img.1), Sense::drag());
+                // While cropping the whole original screenshot is shown, with the annotations
+                // where they are on it
+                let cropping = self.session.modification && self.session.modifier == Modifier::Crop;
+                if !cropping {
+                    self.crop_texture = None;
+                } else if self.crop_texture.is_none() {
+                    self.crop_texture = self
+                        .session
+                        .original
+                        .as_ref()
+                        .map(|original| load_texture(ctx, "original_image", original));
+                }
+                let (texture, offset) = match &self.crop_texture {
+                    Some(texture) if cropping => (texture.clone(), crop::offset(self.session.crop)),
+                    _ => match &self.texture {
+                        Some(texture) => (texture.clone(), Point::default()),
+                        None => return,
+                    },
+                };
+                let viewer = ImageView::new(frame, texture.size_vec2(), self.session.zoom).viewer;
+                let (mut response, mut painter) = ui.allocate_painter(viewer.size(), Sense::drag());
+                let panning = self.zoom_and_pan(ui, &response, frame, texture.size_vec2());
+                let view = ImageView::new(frame, texture.size_vec2(), self.session.zoom);
+                painter.set_clip_rect(view.viewer);
                 painter.image(
-                    self.texture.clone().unwrap().id(),
-                    egui::Rect::from_center_size(
-                        egui::Pos2::new(
-                            (frame.info().window_info.size[0]) / 2.0,
-                            (frame.info().window_info.size[1]) / 2.0,
-                        ),
-                        egui::Vec2::new(dim_img.0, dim_img.1),
-                    ),
+                    texture.id(),
+                    view.rect,
                     egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
                     Color32::WHITE,
                 );
-                // println!("I'm viewing the image!!");
-                //qua vedo e salvo le modifiche che sto effettuando dopo aver schiacciato il bottone
-                if self.modification {
-                    match self.modifier {
+                // The selection is dropped when it is not the current tool anymore, or when
+                // the annotation disappeared because of an undo
+                if !self.session.modification
+                    || self.session.modifier != Modifier::Select
+                    || self
+                        .drawing
+                        .selected
+                        .and_then(|id| self.session.annotations.get(id))
+                        .is_none()
+                {
+                    self.drawing.selected = None;
+                    self.drawing.drag = None;
+                }
+                self.drawing.constrain = ui.input(|i| i.modifiers.shift);
+                if self.session.modifier != Modifier::Text {
+                    self.drawing.editing = None;
+                }
+                // A double click on a text opens it again for editing
+                if self.session.modification
+                    && matches!(
+                        self.session.modifier,
+                        Modifier::NotSelected | Modifier::Select
+                    )
+                    && ui.input(|i| {
+                        i.pointer
+                            .button_double_clicked(egui::PointerButton::Primary)
+                    })
+                {
+                    if let Some(hover_pos) = response.hover_pos() {
+                        let tolerance = view.to_image_length(6.0);
+                        if let Some(id) = selection::hit_test(
+                            &self.session.annotations,
+                            view.to_image(hover_pos),
+                            tolerance,
+                        ) {
+                            self.edit_text(id, &view);
+                        }
+                    }
+                }
+                // The text being written is not in the viewer and can still be dragged
+                if self.session.modification
+                    && (!panning || self.session.modifier == Modifier::Text)
+                {
+                    match self.session.modifier {
                         Modifier::NotSelected => {}
-                        Modifier::Pen => {
-                            //azione
-                            response
-                                .clone()
-                                .on_hover_cursor(egui::output::CursorIcon::PointingHand);
-                            if self.modified_element.pen.is_empty() {
-                                self.modified_element.pen.push(vec![]);
-                            }
-                            let current_line = self.modified_element.pen.last_mut().unwrap();
-
-                            if let Some(pointer_pos) = response.interact_pointer_pos() {
-                                if current_line.last()
-                                    != Some(&(pointer_pos, self.modified_element.stroke))
+                        Modifier::Select => {
+                            let tolerance = view.to_image_length(6.0);
+                            let selected_shape = self
+                                .drawing
+                                .selected
+                                .and_then(|id| self.session.annotations.get(id));
+
+                            if let Some(hover_pos) = response.hover_pos() {
+                                let pos = view.to_image(hover_pos);
+                                let cursor = if selected_shape
+                                    .and_then(|shape| selection::handle_at(shape, pos, tolerance))
+                                    .is_some()
                                 {
-                                    current_line.push((pointer_pos, self.modified_element.stroke));
-                                    response.mark_changed();
-                                }
-                            } else if !current_line.is_empty() {
-                                self.modified_element.pen.push(vec![]);
-                                response.mark_changed();
-                                self.modifications_vector.push(Modifier::Pen);
-                            }
-                        }
-                        Modifier::Rect => {
-                            //azione
-                            response
-                                .clone()
-                                .on_hover_cursor(egui::output::CursorIcon::Crosshair);
-                            if self.modified_element.rect.is_empty() {
-                                self.modified_element.rect.push(vec![]);
+                                    egui::output::CursorIcon::Crosshair
+                                } else if selection::hit_test(
+                                    &self.session.annotations,
+                                    pos,
+                                    tolerance,
+                                )
+                                .is_some()
+                                {
+                                    egui::output::CursorIcon::Move
+                                } else {
+                                    egui::output::CursorIcon::Default
+                                };
+                                response.clone().on_hover_cursor(cursor);
                             }
-                            let current_line = self.modified_element.rect.last_mut().unwrap();
 
                             if let Some(pointer_pos) = response.interact_pointer_pos() {
-                                if current_line.last()
-                                    != Some(&(pointer_pos, self.modified_element.stroke))
-                                {
-                                    current_line.push((pointer_pos, self.modified_element.stroke));
-                                    response.mark_changed();
+                                let pos = view.to_image(pointer_pos);
+                                match self.drawing.drag.as_mut() {
+                                    Some(drag) => drag.to = pos,
+                                    None => {
+                                        // The drag starts on a handle of the selected annotation,
+                                        // or selects the annotation under the pointer
+                                        let handle = selected_shape.and_then(|shape| {
+                                            selection::handle_at(shape, pos, tolerance)
+                                        });
+                                        let id = if handle.is_some() {
+                                            self.drawing.selected
+                                        } else {
+                                            selection::hit_test(
+                                                &self.session.annotations,
+                                                pos,
+                                                tolerance,
+                                            )
+                                        };
+                                        if id != self.drawing.selected {
+                                            if let Some(shape) =
+                                                id.and_then(|id| self.session.annotations.get(id))
+                                            {
+                                                let stroke = view.to_tool_stroke(shape.stroke());
+                                                self.drawing.stroke.color = stroke.color;
+                                                if let Some((fill, rounding)) = shape.fill() {
+                                                    self.drawing.fill = view::to_color32(fill);
+                                                    self.drawing.rounding =
+                                                        view.to_tool_size(rounding);
+                                                }
+                                                match shape {
+                                                    Shape::Text { .. } | Shape::Marker { .. } => {}
+                                                    Shape::Line { dash, .. } => {
+                                                        self.drawing.stroke.width = stroke.width;
+                                                        self.drawing.dash = *dash;
+                                                    }
+                                                    Shape::Arrow { dash, style, .. } => {
+                                                        self.drawing.stroke.width = stroke.width;
+                                                        self.drawing.dash = *dash;
+                                                        self.drawing.arrow = ArrowStyle {
+                                                            size: view.to_tool_size(style.size),
+                                                            ..*style
+                                                        };
+                                                    }
+                                                    Shape::Redact { redaction, .. } => {
+                                                        self.drawing.redaction = *redaction
+                                                    }
+                                                    _ => self.drawing.stroke.width = stroke.width,
+                                                }
+                                            }
+                                        }
+                                        self.drawing.selected = id;
+                                        self.drawing.drag = id.map(|id| Drag {
+                                            id,
+                                            handle,
+                                            from: pos,
+                                            to: pos,
+                                        });
+                                    }
+                                }
+                            } else if let Some(drag) = self.drawing.drag.take() {
+                                if let Some(shape) = self.session.annotations.get(drag.id) {
+                                    if drag.handle.is_some() {
+                                        let shape = drag.preview(shape);
+                                        self.session.reshape_annotation(drag.id, shape);
+                                    } else if drag.to != drag.from {
+                                        self.session.move_annotation(drag.id, drag.to - drag.from);
+                                    }
                                 }
-                            } else if !current_line.is_empty() {
-                                self.modified_element.rect.push(vec![]);
-                                response.mark_changed();
-                                self.modifications_vector.push(Modifier::Rect);
-                            }
-                        }
-                        Modifier::Arrow => {
-                            //azione
-                            response
-                                .clone()
-                                .on_hover_cursor(egui::output::CursorIcon::Crosshair);
-                            if self.modified_element.arrow.is_empty() {
-                                self.modified_element.arrow.push(vec![]);
                             }
-                            let current_line = self.modified_element.arrow.last_mut().unwrap();
 
-                            if let Some(pointer_pos) = response.interact_pointer_pos() {
-                                if current_line.last()
-                                    != Some(&(pointer_pos, self.modified_element.stroke))
-                                {
-                                    current_line.push((pointer_pos, self.modified_element.stroke));
-                                    response.mark_changed();
+                            if let Some(id) = self.drawing.selected {
+                                if ui.input_mut(|i| {
+                                    i.consume_key(egui::Modifiers::NONE, egui::Key::Delete)
+                                }) {
+                                    self.session.delete_annotation(id);
+                                    self.drawing.selected = None;
+                                    self.drawing.drag = None;
                                 }
-                            } else if !current_line.is_empty() {
-                                self.modified_element.arrow.push(vec![]);
-                                response.mark_changed();
-                                self.modifications_vector.push(Modifier::Arrow);
                             }
                         }
-                        Modifier::Line => {
-                            //azione
-                            response
-                                .clone()
-                                .on_hover_cursor(egui::output::CursorIcon::Crosshair);
-                            if self.modified_element.line.is_empty() {
-                                self.modified_element.line.push(vec![]);
-                            }
-
-                            let current_line = self.modified_element.line.last_mut().unwrap();
+                        Modifier::Pen
+                        | Modifier::Rect
+                        | Modifier::Arrow
+                        | Modifier::CurvedArrow
+                        | Modifier::Line
+                        | Modifier::Circle
+                        | Modifier::Ellipse
+                        | Modifier::Redact
+                        | Modifier::Highlight
+                        | Modifier::Marker => {
+                            let cursor = if matches!(
+                                self.session.modifier,
+                                Modifier::Pen | Modifier::Highlight
+                            ) {
+                                egui::output::CursorIcon::PointingHand
+                            } else {
+                                egui::output::CursorIcon::Crosshair
+                            };
+                            response.clone().on_hover_cursor(cursor);
 
                             if let Some(pointer_pos) = response.interact_pointer_pos() {
-                                if current_line.last()
-                                    != Some(&(pointer_pos, self.modified_element.stroke))
-                                {
-                                    current_line.push((pointer_pos, self.modified_element.stroke));
+                                let pos = view.to_image(pointer_pos);
+                                if self.drawing.points.last() != Some(&pos) {
+                                    self.drawing.points.push(pos);
                                     response.mark_changed();
                                 }
-                            } else if !current_line.is_empty() {
-                                self.modified_element.line.push(vec![]);
-                                response.mark_changed();
-                                self.modifications_vector.push(Modifier::Line);
-                            }
-                        }
-                        Modifier::Circle => {
-                            //azione
-                            response
-                                .clone()
-                                .on_hover_cursor(egui::output::CursorIcon::Crosshair);
-                            if self.modified_element.circle.is_empty() {
-                                self.modified_element.circle.push(vec![]);
-                            }
-                            let current_line = self.modified_element.circle.last_mut().unwrap();
-
-                            if let Some(pointer_pos) = response.interact_pointer_pos() {
-                                if current_line.last()
-                                    != Some(&(pointer_pos, self.modified_element.stroke))
+                            } else if !self.drawing.points.is_empty() {
+                                if let Some(shape) =
+                                    self.drawing.shape(&self.session.modifier, &view)
                                 {
-                                    current_line.push((pointer_pos, self.modified_element.stroke));
-                                    response.mark_changed();
+                                    self.session.add_annotation(shape);
                                 }
-                            } else if !current_line.is_empty() {
-                                self.modified_element.circle.push(vec![]);
+                                self.drawing.points.clear();
                                 response.mark_changed();
-                                self.modifications_vector.push(Modifier::Circle);
                             }
                         }
                         Modifier::Text => {
-                            //azione
-                            let res = egui::Area::new("text")
+                            let area = egui::Area::new("text")
                                 .movable(true)
                                 .default_pos(egui::Pos2::new(
                                     (frame.info().window_info.size[0] - 20.0) / 2.0,
                                     (frame.info().window_info.size[1] - 20.0) / 2.0,
                                 ))
-                                .drag_bounds(egui::Rect::from_center_size(
-                                    egui::Pos2::new(
-                                        (frame.info().window_info.size[0]) / 2.0,
-                                        (frame.info().window_info.size[1]) / 2.0,
+                                .drag_bounds(view.viewer)
+                                .order(egui::layers::Order::Foreground);
+                            // A text that is edited again starts where it was
+                            let area = match self.drawing.text_pos.take() {
+                                Some(pos) => area.current_pos(pos),
+                                None => area,
+                            };
+                            let res = area.show(ctx, |ui| {
+                                let shape = self.drawing.text_shape(Point::default());
+                                let (min, max) = selection::bounds(&shape);
+                                let (rect, _) = ui.allocate_exact_size(
+                                    vec2(
+                                        view.to_screen_length(max.x - min.x),
+                                        view.to_screen_length(max.y - min.y),
                                     ),
-                                    egui::Vec2::new(dim_img.0, dim_img.1),
-                                ))
-                                .order(egui::layers::Order::Foreground)
-                                .show(ctx, |ui| {
-                                    ui.vertical(|ui| {
-                                        ui.label(
-                                            egui::RichText::new(format!(
-                                                "{}",
-                                                self.modified_element.text,
-                                            ))
-                                            .color(self.modified_element.stroke.color)
-                                            .size(self.modified_element.stroke.width * 20.0 + 0.1),
-                                        );
-                                    });
-                                });
-                            if self.modified_element.text_modified {
-                                self.modified_element.text_modified = false;
-                                let rectangle = res.response.rect;
-                                self.modified_element.entire_text.push((
-                                    egui::Pos2::new(rectangle.left(), rectangle.top()),
-                                    self.modified_element.text.clone(),
-                                    self.modified_element.stroke.clone(),
-                                ));
-                                self.modified_element.text = "Example".to_string();
-                                self.modifier = Modifier::NotSelected
+                                    Sense::hover(),
+                                );
+                                let shape = self.drawing.text_shape(view.to_image(rect.min));
+                                view::paint_shape(ui.painter(), &view, &shape);
+                            });
+                            if self.drawing.text_modified {
+                                self.drawing.text_modified = false;
+                                let shape = self
+                                    .drawing
+                                    .text_shape(view.to_image(res.response.rect.min));
+                                match self.drawing.editing.take() {
+                                    Some(id) => self.session.reshape_annotation(id, shape),
+                                    None => {
+                                        self.session.add_annotation(shape);
+                                    }
+                                }
+                                self.drawing.text = "Example".to_string();
+                                self.session.modifier = Modifier::NotSelected
                             }
                         }
                         Modifier::Crop => {
-                            let area = egui::Window::new("crop_area")
-                                .title_bar(false)
-                                .default_size(egui::vec2(320.0, 240.0))
-                                .resizable(true)
-                                .movable(true)
-                                .resize(|r| r.min_size(egui::vec2(1.0, 1.0)))
-                                .resize(|r| r.max_size(egui::vec2(dim_img.0, dim_img.1)))
-                                .default_pos(egui::Pos2::new(
-                                    (frame.info().window_info.size[0] - 320.0) / 2.0,
-                                    (frame.info().window_info.size[1] - 240.0) / 2.0,
-                                ))
-                                .drag_bounds(egui::Rect::from_center_size(
-                                    egui::Pos2::new(
-                                        (frame.info().window_info.size[0]) / 2.0,
-                                        (frame.info().window_info.size[1]) / 2.0,
-                                    ),
-                                    egui::Vec2::new(dim_img.0, dim_img.1),
-                                ))
-                                .frame(egui::Frame {
-                                    stroke: egui::Stroke::new(1.5, egui::Color32::WHITE),
-                                    shadow: egui::epaint::Shadow::small_light(),
-                                    ..Default::default()
-                                })
-                                .show(ctx, |ui| {
-                                    ui.allocate_space(ui.available_size());
-                                });
+                            let (width, height) = self
+                                .session
+                                .original
+                                .as_ref()
+                                .map_or((0, 0), |original| original.dimensions());
+                            let area = self.drawing.crop_area;
+                            let corners = crop_corners(area);
+                            let tolerance = view.to_image_length(8.0);
+                            let inside = |pos: Point| {
+                                pos.x >= area.x
+                                    && pos.y >= area.y
+                                    && pos.x <= area.x + area.width
+                                    && pos.y <= area.y + area.height
+                            };
 
-                            let rectangle = area.unwrap().response.rect;
-                            let mut adj = 1.0;
-                            if cfg!(target_os = "windows") {
-                                adj = frame.info().native_pixels_per_point.unwrap();
+                            if let Some(hover_pos) = response.hover_pos() {
+                                let pos = view.to_image(hover_pos);
+                                let cursor = if corners
+                                    .iter()
+                                    .all(|corner| corner.distance(pos) > tolerance)
+                                    && inside(pos)
+                                {
+                                    egui::output::CursorIcon::Move
+                                } else {
+                                    egui::output::CursorIcon::Crosshair
+                                };
+                                response.clone().on_hover_cursor(cursor);
+                            }
+
+                            if let Some(pointer_pos) = response.interact_pointer_pos() {
+                                let pos = view.to_image(pointer_pos);
+                                match self.drawing.crop_drag {
+                                    // A corner resizes the area, inside it is moved and
+                                    // outside a new one is drawn
+                                    None => {
+                                        self.drawing.crop_drag = Some(
+                                            match corners.iter().position(|corner| {
+                                                corner.distance(pos) <= tolerance
+                                            }) {
+                                                Some(corner) => CropDrag::Corner {
+                                                    fixed: corners[(corner + 2) % 4],
+                                                },
+                                                None if inside(pos) => {
+                                                    CropDrag::Move { from: pos, area }
+                                                }
+                                                None => CropDrag::Corner { fixed: pos },
+                                            },
+                                        );
+                                    }
+                                    Some(CropDrag::Corner { fixed }) => {
+                                        self.drawing.crop_area = crop::drag_corner(
+                                            fixed,
+                                            pos,
+                                            self.drawing.crop_aspect,
+                                            width,
+                                            height,
+                                        );
+                                    }
+                                    Some(CropDrag::Move { from, area }) => {
+                                        self.drawing.crop_area =
+                                            crop::move_by(area, pos - from, width, height);
+                                    }
+                                }
+                            } else {
+                                self.drawing.crop_drag = None;
                             }
-                            self.rectangle = Rectangle {
-                                x: (rectangle.left()) * adj,
-                                y: (rectangle.top()) * adj,
-                                width: rectangle.width() * adj,
-                                height: rectangle.height() * adj,
-                            };
                         }
                     }
                 }
-                let pen = self
-                    .modified_element
-                    .pen
-                    .iter()
-                    .filter(|line| line.len() >= 2)
-                    .map(|line| {
-                        let points: Vec<egui::Pos2> = line.iter().map(|p| p.0).collect();
-                        let stroke = line[0].1;
-                        egui::Shape::line(points, stroke)
-                    });
-                let rect = self
-                    .modified_element
-                    .rect
-                    .iter()
-                    .filter(|line| line.len() >= 2)
-                    .map(|line| {
-                        let rect = egui::Rect::from_two_pos(
-                            line.first().unwrap().0,
-                            line.last().unwrap().0,
-                        );
-                        egui::Shape::rect_stroke(rect, egui::Rounding::none(), line[0].1)
-                    });
-                let circle = self
-                    .modified_element
-                    .circle
-                    .iter()
-                    .filter(|line| line.len() >= 2)
-                    .map(|line| {
-                        egui::Shape::circle_stroke(
-                            line.first().unwrap().0,
-                            line.first().unwrap().0.distance(line.last().unwrap().0),
-                            line[0].1,
-                        )
-                    });
-                let line = self
-                    .modified_element
-                    .line
-                    .iter()
-                    .filter(|line| line.len() >= 2)
-                    .map(|line| {
-                        let vec = [line.first().unwrap().0, line.last().unwrap().0];
-                        egui::Shape::line_segment(vec, line[0].1)
-                    });
 
-                for element in self.modified_element.arrow.clone() {
-                    if element.first().is_some() && element.last().is_some() {
-                        let line = element.first().unwrap().0 - element.last().unwrap().0;
-                        painter.arrow(element.first().unwrap().0, -line, element[0].1);
+                // The redactions and the highlights are rendered from the pixels shown in
+                // the viewer
+                let shown = if cropping {
+                    self.session.original.as_ref()
+                } else {
+                    self.session.image.as_ref()
+                };
+                // The annotations painted so far, under the next one like in the export
+                let mut below = Annotations::default();
+                below.numbering = self.session.annotations.numbering;
+                for annotation in self.session.annotations.items() {
+                    let mut shape = match &self.drawing.drag {
+                        Some(drag) if drag.id == annotation.id => drag.preview(&annotation.shape),
+                        _ => annotation.shape.clone(),
+                    };
+                    shape.translate(offset);
+                    // The text being edited is shown by the editor
+                    if self.session.modifier != Modifier::Text
+                        || self.drawing.editing != Some(annotation.id)
+                    {
+                        self.previews
+                            .paint(ctx, &painter, &view, shown, &below, &shape);
+                        if let Some(label) = self.session.annotations.marker_label(annotation.id) {
+                            view::paint_marker_label(&painter, &view, &shape, &label);
+                        }
                     }
+                    let below_annotation = below.create(shape);
+                    below.insert(below.len(), below_annotation);
                 }
-
-                for element in self.modified_element.entire_text.clone() {
-                    painter.text(
-                        element.0,
-                        egui::Align2::LEFT_TOP,
-                        element.1,
-                        egui::FontId::proportional(element.2.width * 20.0 + 0.1),
-                        element.2.color,
-                    );
+                if let Some(annotation) = self
+                    .drawing
+                    .selected
+                    .and_then(|id| self.session.annotations.items().iter().find(|a| a.id == id))
+                {
+                    let shape = match &self.drawing.drag {
+                        Some(drag) => drag.preview(&annotation.shape),
+                        None => annotation.shape.clone(),
+                    };
+                    view::paint_selection(&painter, &view, &shape);
                 }
-
-                painter.extend(pen);
-                painter.extend(line);
-                painter.extend(rect);
-                painter.extend(circle);
+                // The annotation that is being drawn
+                if let Some(shape) = self.drawing.shape(&self.session.modifier, &view) {
+                    self.previews
+                        .paint(ctx, &painter, &view, shown, &below, &shape);
+                    let label = self.session.annotations.next_marker_label();
+                    view::paint_marker_label(&painter, &view, &shape, &label);
+                }
+                self.previews.retain_used();
+                if cropping {
+                    view::paint_crop(&painter, &view, self.drawing.crop_area);
+                }
+                self.zoom_ui(ctx, &view);
             });
 
+        self.session.view_image = view_image;
+
         Window::new("screenshot_area menu_bar")
             .title_bar(false)
             .frame(egui::Frame {
@@ -995,9 +1901,9 @@ impl App for AppUtility {
             ))
             .resizable(false)
             .open(
-                &mut (self.selecting_area.clone()
-                    && !self.timer.form_opened()
-                    && !self.timer.is_running()),
+                &mut (self.session.selecting_area
+                    && !self.session.timer.form_opened()
+                    && !self.session.timer.is_running()),
             )
             .show(ctx, |ui| {
                 ui.with_layout(
@@ -1010,36 +1916,36 @@ impl App for AppUtility {
                         cross_justify: true,
                     },
                     |ui| {
-                        match self.shortcuts.listener(ctx, self.view_image, self.selecting_area) {
-                            Some(action) => self.make_action(action, ctx, frame),
-                            None => {}
-                        }
-                        
-                        if custom_button(
-                            ui,
-                            " 📷  Capture  ",
-                            Color32::WHITE,
-                            Color32::from_rgb(142, 167, 233),
-                        )
-                        .on_hover_text("Capture the area screenshot!")
-                        .clicked()
-                        {
-                            self.make_action(Action::StartTimer, ctx, frame);
+                        if let Some(action) = shortcut::listener(
+                            &self.session.shortcuts,
+                            ctx,
+                            self.session.view_image,
+                            self.session.selecting_area,
+                        ) {
+                            self.make_action(action, ctx, frame);
                         }
 
-                        ui.add_space(10.0);
-                        if custom_button(
-                            ui,
-                            "  TIMER  ",
-                            egui::Color32::DARK_GRAY,
-                            egui::Color32::from_rgb(252, 226, 174),
-                        )
-                        .on_hover_text("Take a screenshot after a delay")
-                        .clicked()
+                        let selected = self
+                            .session
+                            .frozen
+                            .as_ref()
+                            .is_some_and(|frozen| frozen.size(self.session.area).is_some());
+                        if ui
+                            .add_enabled_ui(selected, |ui| {
+                                custom_button(
+                                    ui,
+                                    " 📷  Capture  ",
+                                    Color32::WHITE,
+                                    Color32::from_rgb(142, 167, 233),
+                                )
+                            })
+                            .inner
+                            .on_hover_text("Keep the selected area, or press enter")
+                            .on_disabled_hover_text("Drag on the screen to select an area")
+                            .clicked()
                         {
-                            self.make_action(Action::SetTimer, ctx, frame);
+                            self.make_action(Action::Capture, ctx, frame);
                         }
-                        ui.label(format!("Actual delay: {}", self.timer.seconds));
 
                         ui.add_space(10.0);
                         if custom_button(
@@ -1057,55 +1963,46 @@ impl App for AppUtility {
                 )
             });
 
-        let window = Window::new("selection_area_rectangle")
-            .title_bar(false)
-            .default_size(egui::vec2(500.0, 300.0))
-            .resizable(true)
-            .movable(true)
-            .default_pos(egui::Pos2::new(
-                (frame.info().window_info.size[0] - 500.0) / 2.0,
-                (frame.info().window_info.size[1] - 300.0) / 2.0,
-            ))
-            .resize(|r| {
-                r.max_size(egui::vec2(
-                    frame.info().window_info.size[0],
-                    frame.info().window_info.size[1],
-                ))
-            })
-            .resize(|r| r.min_size(egui::vec2(2.0, 2.0)))
-            .frame(egui::Frame {
-                stroke: egui::Stroke {
-                    width: 1.0,
-                    color: Color32::WHITE,
-                },
-                ..Default::default()
-            })
-            .open(&mut self.selecting_area)
-            .show(ctx, |ui| {
-                ui.allocate_space(ui.available_size());
-                // println!("Am I here?!");
-            });
-
-        if self.selecting_area {
-            // println!("Do I need to be here?");
-            let rect = window.unwrap().response.rect;
-            let mut corr = 1.0;
-            if cfg!(target_os = "windows") {
-                corr = frame.info().native_pixels_per_point.unwrap();
+        // While an area is selected the window is over every screen, so that the area can be
+        // on any of them, and even on more than one
+        if self.session.selecting_area != self.covering_screens {
+            self.covering_screens = self.session.selecting_area;
+            if self.covering_screens {
+                let desktop = self.session.screenshots.desktop();
+                frame.set_maximized(false);
+                frame.set_window_pos(egui::pos2(desktop.x, desktop.y));
+                frame.set_window_size(vec2(desktop.width, desktop.height));
+            } else {
+                frame.set_maximized(true);
             }
-            self.rectangle = Rectangle {
-                x: rect.left() * corr,
-                y: rect.top() * corr,
-                width: rect.width() * corr,
-                height: rect.height() * corr,
+        }
+        // Where the window is on the desktop, in points
+        let window_origin = frame.info().window_info.position.unwrap_or_default();
+        match (self.area_selection.as_mut(), self.session.frozen.as_ref()) {
+            (Some(selection), Some(frozen)) => {
+                selection.show(ctx, frozen, &mut self.session.area, window_origin);
+                let selected = frozen.size(self.session.area).is_some();
+                let (enter, escape) = ctx.input(|i| {
+                    (
+                        i.key_pressed(egui::Key::Enter),
+                        i.key_pressed(egui::Key::Escape),
+                    )
+                });
+                if enter && selected {
+                    self.make_action(Action::Capture, ctx, frame);
+                } else if escape {
+                    self.make_action(Action::HomePage, ctx, frame);
+                }
             }
+            // The textures of the frozen screens are dropped with them
+            _ => self.area_selection = None,
         }
 
-        if self.show_settings && AllShortcuts::is_default(&self.temp_shortcuts) {
-            self.temp_shortcuts = self.shortcuts.clone();
+        if self.session.show_settings && AllShortcuts::is_default(&self.temp_shortcuts) {
+            self.temp_shortcuts = self.session.shortcuts.clone();
         }
 
-        if self.show_settings {
+        if self.session.show_settings {
             Window::new("Settings:")
                 .title_bar(false)
                 .frame(egui::Frame {
@@ -1133,7 +2030,7 @@ impl App for AppUtility {
                             )
                             .clicked()
                             {
-                                self.show_settings = false; // Close the window
+                                self.session.show_settings = false; // Close the window
                             }
                         });
                     });
@@ -1146,7 +2043,7 @@ impl App for AppUtility {
 
                     ui.colored_label(Color32::BLACK, "Saving path for the screenshot: ");
                     ui.horizontal(|ui| {
-                        let set_path_text = ui.text_edit_singleline(&mut self.default_path);
+                        let set_path_text = ui.text_edit_singleline(&mut self.session.default_path);
                         if custom_button(
                             ui,
                             "  Change path  ",
@@ -1155,22 +2052,26 @@ impl App for AppUtility {
                         )
                         .clicked()
                         {
-                            let result = FileDialog::new().show_open_single_dir().unwrap();
-                            if result.is_some() {
-                                self.default_path = result.unwrap().to_string_lossy().to_string();
+                            match FileDialog::new().show_open_single_dir() {
+                                Ok(Some(result)) => {
+                                    self.session.default_path =
+                                        result.to_string_lossy().to_string();
+                                }
+                                Ok(None) => {}
+                                Err(err) => {
+                                    self.report(AppError::Dialog(err.to_string()), None);
+                                }
                             }
                         }
-                        if set_path_text.changed() {
-                            if self.default_path == "" {
-                                self.default_path = "screenshots".to_string();
-                            }
+                        if set_path_text.changed() && self.session.default_path.is_empty() {
+                            self.session.default_path = "screenshots".to_string();
                         }
                     });
                     ui.add_space(15.0);
                     ui.colored_label(Color32::BLACK, "Default name for the screenshot: ");
 
                     ui.horizontal(|ui| {
-                        ui.text_edit_singleline(&mut self.default_name);
+                        ui.text_edit_singleline(&mut self.session.default_name);
                         if custom_button(
                             ui,
                             "  Save name  ",
@@ -1179,8 +2080,8 @@ impl App for AppUtility {
                         )
                         .clicked()
                         {
-                            self.default_name_selected = false;
-                            self.show_settings = false;
+                            self.session.default_name_selected = false;
+                            self.session.show_settings = false;
                         }
                         if custom_button(
                             ui,
@@ -1190,8 +2091,8 @@ impl App for AppUtility {
                         )
                         .clicked()
                         {
-                            self.default_name_selected = true;
-                            self.default_name = build_default_name();
+                            self.session.default_name_selected = true;
+                            self.session.default_name = build_default_name();
                         }
                     });
                     ui.add_space(10.0);
@@ -1201,27 +2102,67 @@ impl App for AppUtility {
                     ui.heading("Change screen numeber settings:");
                     ui.separator();
                     ui.add_space(10.0);
+                    let screenshots = &mut self.session.screenshots;
                     egui::ComboBox::from_id_source("screens_selection")
-                        .selected_text(format!(
-                            "Selected screen: {}",
-                            self.screenshots.screen_number
-                        ))
+                        .selected_text(if screenshots.all_screens {
+                            "Selected screen: all".to_string()
+                        } else {
+                            format!("Selected screen: {}", screenshots.screen_number)
+                        })
                         .show_ui(ui, |ui| {
-                            for i in 0..self.screenshots.total_screens() {
+                            for i in 0..screenshots.total_screens() {
                                 let txt = format!("Screen number {}", i);
-                                ui.selectable_value(&mut self.screenshots.screen_number, i, txt);
+                                if ui
+                                    .selectable_label(
+                                        !screenshots.all_screens && screenshots.screen_number == i,
+                                        txt,
+                                    )
+                                    .clicked()
+                                {
+                                    screenshots.screen_number = i;
+                                    screenshots.all_screens = false;
+                                }
+                            }
+                            if screenshots.total_screens() > 1 {
+                                ui.selectable_value(
+                                    &mut screenshots.all_screens,
+                                    true,
+                                    "All screens",
+                                );
                             }
                         });
                     ui.add_space(10.0);
                     ui.separator();
                     ui.add_space(25.0);
-                    if self.screenshots.screen_number != self.screenshots.default_screen_number {
-                        self.selection_mode = Selection::Fullscreen;
-                        self.selecting_area = false;
-                        self.screenshots.default = false;
-                    } else {
-                        self.screenshots.default = true;
-                    }
+
+                    ui.heading("Saving format settings:");
+                    ui.separator();
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        ui.colored_label(Color32::BLACK, "JPEG quality: ");
+                        ui.add(egui::Slider::new(
+                            &mut self.session.export_settings.jpeg_quality,
+                            1..=100,
+                        ));
+                    });
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        ui.colored_label(Color32::BLACK, "PNG compression level: ");
+                        egui::ComboBox::from_id_source("png_compression")
+                            .selected_text(self.session.export_settings.png_compression.name())
+                            .show_ui(ui, |ui| {
+                                for compression in PngCompression::all() {
+                                    ui.selectable_value(
+                                        &mut self.session.export_settings.png_compression,
+                                        compression,
+                                        compression.name(),
+                                    );
+                                }
+                            });
+                    });
+                    ui.add_space(10.0);
+                    ui.separator();
+                    ui.add_space(25.0);
 
                     ui.heading("Shortcuts settings: ");
                     ui.separator();
@@ -1246,7 +2187,7 @@ impl App for AppUtility {
                                 );
 
                                 ui.horizontal(|ui| {
-                                    ui.checkbox(&mut new_shortcut.shortcut.modifiers.alt, "");
+                                    ui.checkbox(&mut new_shortcut.shortcut.alt, "");
                                     let mut text = String::new();
                                     if cfg!(target_os = "macos") {
                                         text = "Option".to_string();
@@ -1255,10 +2196,10 @@ impl App for AppUtility {
                                     }
                                     ui.label(text);
 
-                                    ui.checkbox(&mut new_shortcut.shortcut.modifiers.shift, "");
+                                    ui.checkbox(&mut new_shortcut.shortcut.shift, "");
                                     ui.label("Shift");
 
-                                    ui.checkbox(&mut new_shortcut.shortcut.modifiers.command, "");
+                                    ui.checkbox(&mut new_shortcut.shortcut.command, "");
                                     text = String::new();
                                     if cfg!(target_os = "macos") {
                                         text = "Cmd".to_string();
@@ -1272,12 +2213,12 @@ impl App for AppUtility {
                                         "key_{}",
                                         new_shortcut.name
                                     )) // Unique ID for each row
-                                    .selected_text(Key::name(new_shortcut.shortcut.key))
+                                    .selected_text(&new_shortcut.shortcut.key)
                                     .show_ui(ui, |ui| {
-                                        for key in &self.shortcuts.all_keys {
+                                        for key in KEY_NAMES {
                                             ui.selectable_value(
                                                 &mut new_shortcut.shortcut.key,
-                                                ShortCut::from_str_to_key(key).unwrap(),
+                                                key.to_string(),
                                                 key,
                                             );
                                         }
@@ -1298,12 +2239,12 @@ impl App for AppUtility {
                     .clicked()
                     {
                         // clone the temp_shortcuts into the shortcuts but only if the shortcuts are valid (no actions with the same shortcut combination )
-                        // if the shortcuts are not valid, show a popup with the error
+                        // if the shortcuts are not valid, report the error
                         if !self.temp_shortcuts.has_duplicate_shortcuts() {
-                            self.shortcuts = self.temp_shortcuts.clone();
-                            self.show_settings = false;
+                            self.session.shortcuts = self.temp_shortcuts.clone();
+                            self.session.show_settings = false;
                         } else {
-                            self.show_error = true;
+                            self.report(AppError::DuplicateShortcuts, None);
                         }
                     }
                     ui.add_space(25.0);
@@ -1311,41 +2252,188 @@ impl App for AppUtility {
         }
 
         // Reset temp_shortcuts when settings window is closed
-        if !self.show_settings {
-            self.temp_shortcuts = self.shortcuts.clone();
-            self.show_error = false;
+        if !self.session.show_settings {
+            self.temp_shortcuts = self.session.shortcuts.clone();
         }
 
-        if self.show_settings {
-            Window::new("Error")
-            .title_bar(true)
-            .open(&mut self.show_error)
-            .resizable(false)
-            .movable(true)
-            .frame(egui::Frame {
-                fill: Color32::RED,
-                stroke: egui::Stroke::new(0.5, egui::Color32::WHITE),
-                inner_margin: egui::style::Margin::same(15.0),
-                rounding: egui::Rounding::same(20.0),
-                ..Default::default()
-            })
-            .default_rect(egui::Rect::from_center_size(
-                egui::Pos2::new(pos_central_x + 70.0, 300.0),
-                egui::Vec2::new(500.0, 70.0),
-            ))
-            .show(ctx, |ui| {
-                ui.add_space(20.0);
-                ui.colored_label(
-                    Color32::WHITE,
-                    "Error: there are some actions with the same shortcut combination",
-                );
-                ui.add_space(10.0);
-            });
+        if let Some(report) = &self.error_report {
+            let mut open = true;
+            let mut retry = false;
+            let mut dismiss = false;
+            Window::new("Something went wrong")
+                .id(egui::Id::new("error_report"))
+                .title_bar(true)
+                .open(&mut open)
+                .resizable(false)
+                .movable(true)
+                .frame(egui::Frame {
+                    fill: Color32::RED,
+                    stroke: egui::Stroke::new(0.5, egui::Color32::WHITE),
+                    inner_margin: egui::style::Margin::same(15.0),
+                    rounding: egui::Rounding::same(20.0),
+                    ..Default::default()
+                })
+                .default_rect(egui::Rect::from_center_size(
+                    egui::Pos2::new(pos_central_x + 70.0, 300.0),
+                    egui::Vec2::new(500.0, 70.0),
+                ))
+                .show(ctx, |ui| {
+                    ui.add_space(20.0);
+                    ui.colored_label(Color32::WHITE, format!("Error: {}", report.error));
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        if report.retry.is_some()
+                            && custom_button(ui, "  Retry  ", Color32::RED, Color32::WHITE)
+                                .clicked()
+                        {
+                            retry = true;
+                        }
+                        if custom_button(ui, "  Dismiss  ", Color32::RED, Color32::WHITE).clicked()
+                        {
+                            dismiss = true;
+                        }
+                    });
+                });
+            if retry {
+                if let Some(retry) = self.error_report.take().and_then(|report| report.retry) {
+                    self.retry(retry, ctx, frame);
+                }
+            } else if !open || dismiss {
+                self.error_report = None;
+            }
+        }
+
+        if self.unredacted_project.is_some() {
+            let mut open = true;
+            let mut save = false;
+            let mut cancel = false;
+            Window::new("Redactions warning")
+                .title_bar(true)
+                .open(&mut open)
+                .resizable(false)
+                .movable(true)
+                .frame(egui::Frame {
+                    fill: Color32::from_rgb(252, 226, 174),
+                    stroke: egui::Stroke::new(0.5, egui::Color32::DARK_GRAY),
+                    inner_margin: egui::style::Margin::same(15.0),
+                    rounding: egui::Rounding::same(20.0),
+                    ..Default::default()
+                })
+                .default_rect(egui::Rect::from_center_size(
+                    egui::Pos2::new(pos_central_x + 70.0, 250.0),
+                    egui::Vec2::new(500.0, 70.0),
+                ))
+                .show(ctx, |ui| {
+                    ui.add_space(20.0);
+                    ui.colored_label(
+                        Color32::DARK_GRAY,
+                        "Warning: the project keeps the original screenshot, so anyone who \
+                         opens it can see the pixels under the redactions.\nExport an image to \
+                         share it with the redactions applied.",
+                    );
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        save = custom_button(
+                            ui,
+                            "  Save anyway  ",
+                            Color32::DARK_GRAY,
+                            Color32::WHITE,
+                        )
+                        .clicked();
+                        cancel =
+                            custom_button(ui, "  Cancel  ", Color32::DARK_GRAY, Color32::WHITE)
+                                .clicked();
+                    });
+                });
+            if save {
+                if let Some(path) = self.unredacted_project.take() {
+                    self.save_to(path);
+                }
+            } else if !open || cancel {
+                self.unredacted_project = None;
+            }
+        }
+
+        if self.config_warning.is_some() {
+            let mut open = true;
+            Window::new("Settings warning")
+                .title_bar(true)
+                .open(&mut open)
+                .resizable(false)
+                .movable(true)
+                .frame(egui::Frame {
+                    fill: Color32::from_rgb(252, 226, 174),
+                    stroke: egui::Stroke::new(0.5, egui::Color32::DARK_GRAY),
+                    inner_margin: egui::style::Margin::same(15.0),
+                    rounding: egui::Rounding::same(20.0),
+                    ..Default::default()
+                })
+                .default_rect(egui::Rect::from_center_size(
+                    egui::Pos2::new(pos_central_x + 70.0, 200.0),
+                    egui::Vec2::new(500.0, 70.0),
+                ))
+                .show(ctx, |ui| {
+                    ui.add_space(20.0);
+                    ui.colored_label(Color32::DARK_GRAY, self.config_warning.as_deref().unwrap());
+                    ui.add_space(10.0);
+                });
+            if !open {
+                self.config_warning = None;
+            }
+        }
+
+        if self.config_file == ConfigFile::Unreadable && self.session.config() != self.saved_config
+        {
+            let mut replace = false;
+            let mut keep = false;
+            Window::new("Settings file")
+                .title_bar(true)
+                .resizable(false)
+                .movable(true)
+                .frame(egui::Frame {
+                    fill: Color32::from_rgb(252, 226, 174),
+                    stroke: egui::Stroke::new(0.5, egui::Color32::DARK_GRAY),
+                    inner_margin: egui::style::Margin::same(15.0),
+                    rounding: egui::Rounding::same(20.0),
+                    ..Default::default()
+                })
+                .default_rect(egui::Rect::from_center_size(
+                    egui::Pos2::new(pos_central_x + 70.0, 300.0),
+                    egui::Vec2::new(500.0, 70.0),
+                ))
+                .show(ctx, |ui| {
+                    ui.add_space(20.0);
+                    ui.colored_label(
+                        Color32::DARK_GRAY,
+                        "The settings file could not be loaded. Saving the changed settings \
+                         replaces it, and what it holds is lost.",
+                    );
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        replace =
+                            custom_button(ui, "  Replace it  ", Color32::DARK_GRAY, Color32::WHITE)
+                                .clicked();
+                        keep = custom_button(
+                            ui,
+                            "  Keep it, don't save  ",
+                            Color32::DARK_GRAY,
+                            Color32::WHITE,
+                        )
+                        .clicked();
+                    });
+                });
+            if replace {
+                self.config_file = ConfigFile::Writable;
+            } else if keep {
+                self.config_file = ConfigFile::Kept;
+            }
         }
 
+        self.persist_config(ctx);
+
         Window::new("Timer form")
             .title_bar(false)
-            .open(&mut self.timer.form_opened())
+            .open(&mut self.session.timer.form_opened())
             .movable(true)
             .resizable(false)
             .frame(egui::Frame {
@@ -1362,7 +2450,7 @@ impl App for AppUtility {
             .show(ctx, |ui| {
                 ui.label("Timer (in seconds)");
                 ui.add_space(20.0);
-                ui.add(egui::DragValue::new(&mut self.timer.seconds).clamp_range(0..=60));
+                ui.add(egui::DragValue::new(&mut self.session.timer.seconds).clamp_range(0..=60));
                 ui.add_space(10.0);
                 ui.horizontal(|ui| {
                     if custom_button(
@@ -1373,7 +2461,7 @@ impl App for AppUtility {
                     )
                     .clicked()
                     {
-                        self.timer.close_form();
+                        self.session.timer.close_form();
                     }
                     ui.add_space(5.0);
                     if custom_button(
@@ -1391,7 +2479,7 @@ impl App for AppUtility {
 
         Window::new("Timer running")
             .title_bar(false)
-            .open(&mut (self.timer.is_running() && !self.hide))
+            .open(&mut (self.session.timer.is_running() && !self.session.hide))
             .movable(false)
             .resizable(false)
             .default_size(egui::vec2(500.0, 500.0))
@@ -1403,11 +2491,11 @@ impl App for AppUtility {
             })
             .show(ctx, |ui| {
                 ui.label(
-                    egui::RichText::new(" ".to_owned() + &self.timer.seconds.to_string())
+                    egui::RichText::new(" ".to_owned() + &self.session.timer.seconds.to_string())
                         .size(50.0)
                         .color(egui::Color32::DARK_GRAY),
                 );
-                if self.timer.is_running() {
+                if self.session.timer.is_running() {
                     self.make_action(Action::ManageTimer, ctx, frame);
                 }
                 if custom_button(
@@ -1464,47 +2552,52 @@ fn custom_button(
     custom_button_with_font_size(ui, text, text_color, bg_color, 13.0)
 }
 
-fn build_default_name() -> String {
-    let now = Local::now()
-        .to_string()
-        .replace("-", "")
-        .replace(":", "_")
-        .replace(" ", "-");
-    format!("Screenshot_{}", now)[..28].to_string()
+fn load_texture(ctx: &egui::Context, name: &str, image: &RgbaImage) -> TextureHandle {
+    ctx.load_texture(
+        name,
+        egui::ColorImage::from_rgba_unmultiplied(
+            [image.width() as usize, image.height() as usize],
+            image.as_raw(),
+        ),
+        // The pixels stay sharp when the viewer is zoomed in
+        egui::TextureOptions {
+            magnification: egui::TextureFilter::Nearest,
+            minification: egui::TextureFilter::Linear,
+        },
+    )
+}
+
+fn crop_corners(area: Rectangle) -> [Point; 4] {
+    [
+        Point::new(area.x, area.y),
+        Point::new(area.x + area.width, area.y),
+        Point::new(area.x + area.width, area.y + area.height),
+        Point::new(area.x, area.y + area.height),
+    ]
 }
 
-fn resize_to_fit_container(
-    container_width: f32,
-    container_height: f32,
-    image_width: f32,
-    image_height: f32,
-) -> (f32, f32) {
-    let container_ratio = container_width / container_height;
-    let image_ratio = image_width / image_height;
-
-    if container_ratio > image_ratio {
-        let new_height = container_height;
-        let new_width = new_height * image_ratio;
-        (new_width, new_height)
-    } else {
-        let new_width = container_width;
-        let new_height = new_width / image_ratio;
-        (new_width, new_height)
+fn save_file_dialog<'a>(location: &'a std::path::Path, filename: &'a str) -> FileDialog<'a> {
+    let mut dialog = FileDialog::new()
+        .set_location(location)
+        .set_filename(filename);
+    for format in ExportFormat::all() {
+        dialog = dialog.add_filter(format.name(), format.extensions());
     }
+    dialog
+        .add_filter(SVG_NAME, &[SVG_EXTENSION])
+        .add_filter(PROJECT_NAME, &[PROJECT_EXTENSION])
 }
 
-fn load_image_from_mem(image_data: &[u8]) -> Result<egui::ColorImage, ImageError> {
-    let image = load_from_memory(image_data)?;
-    let size = [image.width() as _, image.height() as _];
-    let image_buffer = image.to_rgba8();
-    let pixels = image_buffer.as_flat_samples();
-    Ok(egui::ColorImage::from_rgba_unmultiplied(
-        size,
-        pixels.as_slice(),
-    ))
+// The images that can be opened have the formats they can be saved in
+fn open_file_dialog<'a>(location: &'a std::path::Path, extensions: &'a [&str]) -> FileDialog<'a> {
+    FileDialog::new()
+        .set_location(location)
+        .add_filter("Image", extensions)
+        .add_filter(PROJECT_NAME, &[PROJECT_EXTENSION])
 }
 
-pub fn window() -> eframe::Result<()> {
+// `file` is opened in the editor as soon as the window is shown
+pub fn window(screenshots: Screenshots, file: Option<PathBuf>) -> eframe::Result<()> {
     // Set the main window configuration options
     let options = eframe::NativeOptions {
         maximized: true,
@@ -1517,6 +2610,8 @@ pub fn window() -> eframe::Result<()> {
     run_native(
         "AppUtility",
         options,
-        Box::new(|cc: &eframe::CreationContext<'_>| Box::new(AppUtility::new(cc))),
+        Box::new(|cc: &eframe::CreationContext<'_>| {
+            Box::new(AppUtility::new(cc, screenshots, file))
+        }),
     )
 }
@@ -0,0 +1,475 @@
+use app_utility::core::{
+    annotation::{Annotations, Color, Point, Shape, Stroke},
+    capture::Rectangle,
+    path::{self, LinePath},
+    rasterizer, scaling, selection, text,
+    zoom::Zoom,
+};
+use eframe::{
+    egui::{self, Color32, Painter, Pos2, Rect, TextureHandle, Vec2},
+    Frame,
+};
+use image::RgbaImage;
+
+// Maps the points of the window where the screenshot is shown to the pixels of the screenshot
+#[derive(Clone, Copy)]
+pub struct ImageView {
+    // Where the whole screenshot is, larger than the viewer when it is zoomed in
+    pub rect: Rect,
+    // The part of the window the screenshot is shown in
+    pub viewer: Rect,
+    // Pixels of the screenshot per point of the window
+    scale: f32,
+    // The same when the screenshot fits the viewer. The sizes chosen in the toolbar are
+    // relative to it, so that the annotations do not depend on the zoom
+    fit_scale: f32,
+}
+
+impl ImageView {
+    pub fn new(frame: &Frame, image_size: Vec2, zoom: Zoom) -> Self {
+        let dim_img = resize_to_fit_container(
+            frame.info().window_info.size.x / 3.0 * 2.0,
+            frame.info().window_info.size.y / 3.0 * 2.0,
+            image_size[0],
+            image_size[1],
+        );
+        let viewer = Rect::from_center_size(
+            Pos2::new(
+                (frame.info().window_info.size[0]) / 2.0,
+                (frame.info().window_info.size[1]) / 2.0,
+            ),
+            Vec2::new(dim_img.0, dim_img.1),
+        );
+        let image = Point::new(image_size.x, image_size.y);
+        let size = Point::new(viewer.width(), viewer.height());
+        let points_per_pixel = zoom.scale(image, size);
+        let origin = zoom.origin(image, size);
+        Self {
+            rect: Rect::from_min_size(
+                viewer.min + Vec2::new(origin.x, origin.y),
+                image_size * points_per_pixel,
+            ),
+            viewer,
+            scale: 1.0 / points_per_pixel,
+            fit_scale: image_size[0] / viewer.width(),
+        }
+    }
+
+    pub fn to_image(self, pos: Pos2) -> Point {
+        let offset = pos - self.rect.min;
+        Point::new(
+            self.to_image_length(offset.x),
+            self.to_image_length(offset.y),
+        )
+    }
+
+    pub fn to_screen(self, point: Point) -> Pos2 {
+        self.rect.min
+            + Vec2::new(
+                self.to_screen_length(point.x),
+                self.to_screen_length(point.y),
+            )
+    }
+
+    pub fn to_image_length(self, length: f32) -> f32 {
+        scaling::to_pixels(length, self.scale)
+    }
+
+    pub fn to_screen_length(self, length: f32) -> f32 {
+        scaling::to_points(length, self.scale)
+    }
+
+    // A size chosen in the toolbar, in pixels of the screenshot
+    pub fn to_image_size(self, size: f32) -> f32 {
+        scaling::to_pixels(size, self.fit_scale)
+    }
+
+    // A size of an annotation as it is shown in the toolbar
+    pub fn to_tool_size(self, size: f32) -> f32 {
+        scaling::to_points(size, self.fit_scale)
+    }
+
+    // The stroke chosen in the toolbar
+    pub fn to_image_stroke(self, stroke: egui::Stroke) -> Stroke {
+        Stroke::new(self.to_image_size(stroke.width), to_color(stroke.color))
+    }
+
+    pub fn to_tool_stroke(self, stroke: Stroke) -> egui::Stroke {
+        egui::Stroke::new(self.to_tool_size(stroke.width), to_color32(stroke.color))
+    }
+
+    pub fn to_screen_stroke(self, stroke: Stroke) -> egui::Stroke {
+        egui::Stroke::new(
+            self.to_screen_length(stroke.width),
+            to_color32(stroke.color),
+        )
+    }
+
+    // A position in the viewer, relative to its top left corner
+    pub fn to_viewer(self, pos: Pos2) -> Point {
+        let offset = pos - self.viewer.min;
+        Point::new(offset.x, offset.y)
+    }
+
+    pub fn viewer_size(self) -> Point {
+        Point::new(self.viewer.width(), self.viewer.height())
+    }
+}
+
+pub fn to_color(color: Color32) -> Color {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    Color::rgba(r, g, b, a)
+}
+
+pub fn to_color32(color: Color) -> Color32 {
+    Color32::from_rgba_unmultiplied(color.r, color.g, color.b, color.a)
+}
+
+// Sides of the polygon that approximates the ellipses
+const ELLIPSE_SEGMENTS: usize = 64;
+
+// Paints an annotation of the screenshot in the viewer
+pub fn paint_shape(painter: &Painter, view: &ImageView, shape: &Shape) {
+    match shape {
+        Shape::Pen { points, stroke } => {
+            if points.len() >= 2 {
+                let points = points.iter().map(|p| view.to_screen(*p)).collect();
+                painter.add(egui::Shape::line(points, view.to_screen_stroke(*stroke)));
+            }
+        }
+        Shape::Line { stroke, .. } | Shape::Arrow { stroke, .. } => {
+            if let Some(line_path) = path::line_path(shape) {
+                paint_line_path(painter, view, &line_path, *stroke);
+            }
+        }
+        Shape::Rect {
+            start,
+            end,
+            stroke,
+            fill,
+            rounding,
+        } => {
+            painter.rect(
+                Rect::from_two_pos(view.to_screen(*start), view.to_screen(*end)),
+                egui::Rounding::same(view.to_screen_length(*rounding)),
+                to_color32(*fill),
+                view.to_screen_stroke(*stroke),
+            );
+        }
+        Shape::Circle {
+            center,
+            radius,
+            stroke,
+            fill,
+        } => {
+            painter.circle(
+                view.to_screen(*center),
+                view.to_screen_length(*radius),
+                to_color32(*fill),
+                view.to_screen_stroke(*stroke),
+            );
+        }
+        Shape::Ellipse {
+            start,
+            end,
+            stroke,
+            fill,
+        } => {
+            let rect = Rect::from_two_pos(view.to_screen(*start), view.to_screen(*end));
+            let radii = rect.size() / 2.0;
+            let points = (0..ELLIPSE_SEGMENTS)
+                .map(|i| {
+                    let angle = i as f32 / ELLIPSE_SEGMENTS as f32 * std::f32::consts::TAU;
+                    rect.center() + Vec2::new(radii.x * angle.cos(), radii.y * angle.sin())
+                })
+                .collect();
+            painter.add(egui::Shape::convex_polygon(
+                points,
+                to_color32(*fill),
+                view.to_screen_stroke(*stroke),
+            ));
+        }
+        Shape::Text { pos, text, style } => {
+            let layout = text::layout(text, style);
+            painter.rect_filled(
+                Rect::from_min_max(view.to_screen(*pos), view.to_screen(*pos + layout.size)),
+                egui::Rounding::none(),
+                to_color32(style.background),
+            );
+            let font = egui::FontId::new(
+                view.to_screen_length(style.size),
+                egui::FontFamily::Name(style.font_name().into()),
+            );
+            for (offset, line) in layout.lines {
+                painter.text(
+                    view.to_screen(*pos + offset),
+                    egui::Align2::LEFT_TOP,
+                    line,
+                    font.clone(),
+                    to_color32(style.color),
+                );
+            }
+        }
+        // The label is painted by `paint_marker_label`
+        Shape::Marker {
+            center,
+            radius,
+            color,
+        } => {
+            painter.circle_filled(
+                view.to_screen(*center),
+                view.to_screen_length(*radius),
+                to_color32(*color),
+            );
+        }
+        // Only where they are, their pixels are painted by `RasterPreviews`
+        Shape::Highlight { points, stroke } => {
+            if points.len() >= 2 {
+                let points = points.iter().map(|p| view.to_screen(*p)).collect();
+                painter.add(egui::Shape::line(points, view.to_screen_stroke(*stroke)));
+            }
+        }
+        Shape::Redact { start, end, .. } => {
+            painter.rect_filled(
+                Rect::from_two_pos(view.to_screen(*start), view.to_screen(*end)),
+                egui::Rounding::none(),
+                Color32::from_gray(128),
+            );
+        }
+    }
+}
+
+// Paints the pieces with round ends, like the export has them
+fn paint_line_path(painter: &Painter, view: &ImageView, line_path: &LinePath, stroke: Stroke) {
+    let stroke = view.to_screen_stroke(stroke);
+    let round_end = |point: Point| {
+        painter.circle_filled(view.to_screen(point), stroke.width / 2.0, stroke.color);
+    };
+    for piece in &line_path.pieces {
+        if piece.len() >= 2 {
+            let points = piece.iter().map(|p| view.to_screen(*p)).collect();
+            painter.add(egui::Shape::line(points, stroke));
+        }
+        if let (Some(first), Some(last)) = (piece.first(), piece.last()) {
+            round_end(*first);
+            if last != first {
+                round_end(*last);
+            }
+        }
+    }
+    for head in &line_path.heads {
+        let points = [head.sides[0], head.tip, head.sides[1]]
+            .iter()
+            .map(|p| view.to_screen(*p))
+            .collect();
+        if head.filled {
+            painter.add(egui::Shape::convex_polygon(
+                points,
+                stroke.color,
+                egui::Stroke::NONE,
+            ));
+        } else {
+            painter.add(egui::Shape::line(points, stroke));
+            for point in [head.sides[0], head.tip, head.sides[1]] {
+                round_end(point);
+            }
+        }
+    }
+}
+
+// Writes the label of a marker on its badge, where the exported image has it
+pub fn paint_marker_label(painter: &Painter, view: &ImageView, shape: &Shape, label: &str) {
+    if let Shape::Marker {
+        center,
+        radius,
+        color,
+    } = shape
+    {
+        let (top_left, size, text_color) =
+            rasterizer::marker_label(*center, *radius, label, *color);
+        painter.text(
+            view.to_screen(top_left),
+            egui::Align2::LEFT_TOP,
+            label,
+            egui::FontId::proportional(view.to_screen_length(size)),
+            to_color32(text_color),
+        );
+    }
+}
+
+// The redactions and the highlights as they are exported, rendered from the pixels of the
+// screenshot with the annotations under them, since the painter cannot blur nor multiply
+// colors. They are computed again only when the shape, what is under it or the screenshot
+// changes
+#[derive(Default)]
+pub struct RasterPreviews {
+    previews: Vec<RasterPreview>,
+}
+
+struct RasterPreview {
+    shape: Shape,
+    // The annotations under the shape, burned in the pixels it is rendered from
+    below: Vec<Shape>,
+    // Top left and bottom right corners of the rendered pixels, with their texture
+    texture: Option<(Point, Point, TextureHandle)>,
+    used: bool,
+}
+
+impl RasterPreviews {
+    // Paints a shape of the screenshot `image` over the annotations `below` it, with the
+    // rendered pixels if the painter cannot paint it
+    pub fn paint(
+        &mut self,
+        ctx: &egui::Context,
+        painter: &Painter,
+        view: &ImageView,
+        image: Option<&RgbaImage>,
+        below: &Annotations,
+        shape: &Shape,
+    ) {
+        let image = match (shape, image) {
+            (Shape::Redact { .. } | Shape::Highlight { .. }, Some(image)) => image,
+            _ => {
+                paint_shape(painter, view, shape);
+                return;
+            }
+        };
+        let index = match self
+            .previews
+            .iter()
+            .position(|preview| preview.shape == *shape && below.shapes().eq(&preview.below))
+        {
+            Some(index) => index,
+            None => {
+                let texture =
+                    rasterizer::rendered_over(image, below, shape).map(|(x, y, pixels)| {
+                        let min = Point::new(x as f32, y as f32);
+                        let max = min + Point::new(pixels.width() as f32, pixels.height() as f32);
+                        let texture = ctx.load_texture(
+                            "raster_preview",
+                            egui::ColorImage::from_rgba_unmultiplied(
+                                [pixels.width() as usize, pixels.height() as usize],
+                                pixels.as_raw(),
+                            ),
+                            Default::default(),
+                        );
+                        (min, max, texture)
+                    });
+                self.previews.push(RasterPreview {
+                    shape: shape.clone(),
+                    below: below.shapes().cloned().collect(),
+                    texture,
+                    used: false,
+                });
+                self.previews.len() - 1
+            }
+        };
+        let preview = &mut self.previews[index];
+        preview.used = true;
+        if let Some((min, max, texture)) = &preview.texture {
+            painter.image(
+                texture.id(),
+                Rect::from_min_max(view.to_screen(*min), view.to_screen(*max)),
+                Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+                Color32::WHITE,
+            );
+        }
+    }
+
+    // Forgets the previews that were not painted since the last call, e.g. those of the
+    // positions a shape had while it was dragged
+    pub fn retain_used(&mut self) {
+        self.previews.retain(|preview| preview.used);
+        for preview in self.previews.iter_mut() {
+            preview.used = false;
+        }
+    }
+
+    // The screenshot changed, so every preview has to be rendered again
+    pub fn clear(&mut self) {
+        self.previews.clear();
+    }
+}
+
+// Darkens what the crop leaves out and paints the frame of the crop area
+pub fn paint_crop(painter: &Painter, view: &ImageView, area: Rectangle) {
+    let crop = Rect::from_min_max(
+        view.to_screen(Point::new(area.x, area.y)),
+        view.to_screen(Point::new(area.x + area.width, area.y + area.height)),
+    );
+    paint_area(painter, view.rect, crop);
+}
+
+// Darkens what is outside of `area` in `full` and paints the frame of `area`, with the
+// corners that resize it
+pub fn paint_area(painter: &Painter, full: Rect, area: Rect) {
+    let outside = Color32::from_black_alpha(150);
+    for rect in [
+        Rect::from_min_max(full.min, egui::pos2(full.max.x, area.min.y)),
+        Rect::from_min_max(egui::pos2(full.min.x, area.max.y), full.max),
+        Rect::from_min_max(
+            egui::pos2(full.min.x, area.min.y),
+            egui::pos2(area.min.x, area.max.y),
+        ),
+        Rect::from_min_max(
+            egui::pos2(area.max.x, area.min.y),
+            egui::pos2(full.max.x, area.max.y),
+        ),
+    ] {
+        painter.rect_filled(rect, egui::Rounding::none(), outside);
+    }
+    painter.rect_stroke(
+        area,
+        egui::Rounding::none(),
+        egui::Stroke::new(1.5, Color32::WHITE),
+    );
+    for corner in [
+        area.left_top(),
+        area.right_top(),
+        area.right_bottom(),
+        area.left_bottom(),
+    ] {
+        painter.rect_filled(
+            Rect::from_center_size(corner, Vec2::splat(8.0)),
+            egui::Rounding::none(),
+            Color32::WHITE,
+        );
+    }
+}
+
+// Paints the box around the selected annotation and the handles to resize it
+pub fn paint_selection(painter: &Painter, view: &ImageView, shape: &Shape) {
+    let (min, max) = selection::bounds(shape);
+    let margin = view.to_screen_length(shape.stroke().width / 2.0) + 3.0;
+    let color = Color32::from_rgb(0, 120, 215);
+    painter.rect_stroke(
+        Rect::from_min_max(view.to_screen(min), view.to_screen(max)).expand(margin),
+        egui::Rounding::none(),
+        egui::Stroke::new(1.0, color),
+    );
+    for handle in selection::handles(shape) {
+        let rect = Rect::from_center_size(view.to_screen(handle), Vec2::splat(8.0));
+        painter.rect_filled(rect, egui::Rounding::none(), Color32::WHITE);
+        painter.rect_stroke(rect, egui::Rounding::none(), egui::Stroke::new(1.0, color));
+    }
+}
+
+pub fn resize_to_fit_container(
+    container_width: f32,
+    container_height: f32,
+    image_width: f32,
+    image_height: f32,
+) -> (f32, f32) {
+    let container_ratio = container_width / container_height;
+    let image_ratio = image_width / image_height;
+
+    if container_ratio > image_ratio {
+        let new_height = container_height;
+        let new_width = new_height * image_ratio;
+        (new_width, new_height)
+    } else {
+        let new_width = container_width;
+        let new_height = new_width / image_ratio;
+        (new_width, new_height)
+    }
+}
@@ -1,5 +1,38 @@
+mod cli;
 mod gui;
 
-fn main() {
-    gui::window().unwrap();
+use clap::Parser;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    // Failures are logged on stderr, RUST_LOG can make the log more detailed
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+
+    let cli = cli::Cli::parse();
+    let screenshots = match cli.screenshots() {
+        Ok(screenshots) => screenshots,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+    match cli.command {
+        Some(command) => match cli::run(command, screenshots) {
+            Ok(path) => {
+                println!("{}", path.display());
+                ExitCode::SUCCESS
+            }
+            Err(err) => {
+                eprintln!("error: {}", err);
+                ExitCode::FAILURE
+            }
+        },
+        None => match gui::window(screenshots, cli.file) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("error: {}", err);
+                ExitCode::FAILURE
+            }
+        },
+    }
 }
@@ -0,0 +1,109 @@
+use app_utility::core::{
+    annotation::{Annotations, ArrowHead, ArrowStyle, Color, Dash, Point, Shape, Stroke},
+    path, selection,
+};
+use image::RgbaImage;
+
+mod common;
+
+use common::render;
+
+fn arrow(head: ArrowHead, both_ends: bool, control: Option<Point>, dash: Dash) -> Shape {
+    Shape::Arrow {
+        start: Point::new(20.0, 50.0),
+        end: Point::new(180.0, 50.0),
+        control,
+        stroke: Stroke::new(2.0, Color::BLACK),
+        dash,
+        style: ArrowStyle {
+            head,
+            both_ends,
+            size: 30.0,
+        },
+    }
+}
+
+fn is_dark(image: &RgbaImage, x: u32, y: u32) -> bool {
+    image.get_pixel(x, y).0[0] < 100
+}
+
+#[test]
+fn heads_do_not_depend_on_the_width() {
+    let image = render(arrow(ArrowHead::Open, false, None, Dash::Solid), 200, 100);
+    // The sides are 30 pixels long at 30 degrees from the line, even with a thin stroke
+    let side = Point::new(180.0, 50.0) - Point::new(30.0f32.to_radians().cos() * 25.0, 12.5);
+    assert!(is_dark(&image, side.x as u32, side.y as u32));
+    // An open head is empty
+    assert!(!is_dark(&image, 170, 46));
+
+    let image = render(arrow(ArrowHead::Filled, false, None, Dash::Solid), 200, 100);
+    assert!(is_dark(&image, 170, 46));
+    assert!(!is_dark(&image, 25, 42));
+}
+
+#[test]
+fn heads_on_both_ends_or_none() {
+    let path = path::line_path(&arrow(ArrowHead::Filled, true, None, Dash::Solid)).unwrap();
+    let tips: Vec<Point> = path.heads.iter().map(|head| head.tip).collect();
+    assert_eq!(tips, vec![Point::new(180.0, 50.0), Point::new(20.0, 50.0)]);
+    // The line stops inside of the filled heads
+    let piece = &path.pieces[0];
+    assert!(piece[0].x > 20.0 && piece[piece.len() - 1].x < 180.0);
+
+    let path = path::line_path(&arrow(ArrowHead::None, true, None, Dash::Solid)).unwrap();
+    assert!(path.heads.is_empty());
+    assert_eq!(
+        path.pieces,
+        vec![vec![Point::new(20.0, 50.0), Point::new(180.0, 50.0)]]
+    );
+}
+
+#[test]
+fn dashes_and_dots_leave_gaps() {
+    let line = |dash| Shape::Line {
+        start: Point::new(10.0, 20.0),
+        end: Point::new(190.0, 20.0),
+        stroke: Stroke::new(2.0, Color::BLACK),
+        dash,
+    };
+    let gaps = |image: &RgbaImage| (10..190).filter(|x| !is_dark(image, *x, 20)).count();
+
+    assert_eq!(gaps(&render(line(Dash::Solid), 200, 100)), 0);
+    assert!(gaps(&render(line(Dash::Dashed), 200, 100)) > 40);
+    assert!(gaps(&render(line(Dash::Dotted), 200, 100)) > 40);
+
+    let dotted = path::line_path(&line(Dash::Dotted)).unwrap();
+    assert!(dotted.pieces.len() > 10);
+    assert!(dotted.pieces.iter().all(|piece| piece.len() == 1));
+    let dashed = path::line_path(&line(Dash::Dashed)).unwrap();
+    assert!(dashed.pieces.iter().all(|piece| piece.len() == 2));
+}
+
+#[test]
+fn curved_arrows_follow_their_control_point() {
+    let control = Point::new(100.0, 90.0);
+    let mut shape = arrow(ArrowHead::Open, false, Some(control), Dash::Solid);
+    // The middle of the curve is halfway between the middle of the ends and the control
+    let image = render(shape.clone(), 200, 100);
+    assert!(is_dark(&image, 100, 70));
+    assert!(!is_dark(&image, 100, 50));
+    let mut annotations = Annotations::default();
+    let annotation = annotations.create(shape.clone());
+    let id = annotation.id;
+    annotations.insert(0, annotation);
+    assert_eq!(
+        selection::hit_test(&annotations, Point::new(100.0, 70.0), 2.0),
+        Some(id)
+    );
+    assert_eq!(
+        selection::hit_test(&annotations, Point::new(100.0, 50.0), 2.0),
+        None
+    );
+
+    // The control point is the third handle and it moves with the arrow
+    assert_eq!(selection::handles(&shape)[2], control);
+    let bent = selection::resized(&shape, 2, Point::new(100.0, 10.0));
+    assert!(matches!(bent, Shape::Arrow { control: Some(p), .. } if p == Point::new(100.0, 10.0)));
+    shape.translate(Point::new(5.0, 5.0));
+    assert_eq!(selection::handles(&shape)[2], Point::new(105.0, 95.0));
+}
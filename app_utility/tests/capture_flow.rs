@@ -0,0 +1,339 @@
+use app_utility::core::{
+    actions::Action,
+    annotation::{Color, Dash, Point, Shape, Stroke},
+    capture::{composite, Rectangle, Screenshots},
+    error::AppError,
+    mock::{screen_info, synthetic_image, MockBackend},
+    session::{Effect, Selection, Session},
+};
+
+mod common;
+
+use common::output_dir;
+
+fn session() -> Session {
+    let backend = MockBackend::synthetic(&[
+        screen_info(0, 0, 0, 200, 100, 1.0),
+        screen_info(1, 200, 0, 100, 50, 2.0),
+    ]);
+    Session::new(Screenshots::with_backend(Box::new(backend)).unwrap())
+}
+
+fn capture(session: &mut Session) {
+    assert_eq!(
+        session.dispatch(Action::Capture).unwrap(),
+        vec![Effect::HideWindow]
+    );
+    assert_eq!(
+        session.capture().unwrap(),
+        vec![Effect::ImageChanged, Effect::ShowWindow]
+    );
+    assert!(session.view_image);
+}
+
+// Freezes the screens for an area shot, then cuts `area` from them
+fn capture_area(session: &mut Session, area: Rectangle) {
+    session.dispatch(Action::SelectArea).unwrap();
+    assert_eq!(session.selection_mode, Selection::Area);
+    assert_eq!(
+        session.dispatch(Action::Capture).unwrap(),
+        vec![Effect::HideWindow]
+    );
+    assert_eq!(
+        session.capture().unwrap(),
+        vec![Effect::DesktopFrozen, Effect::ShowWindow]
+    );
+    assert!(session.selecting_area);
+    session.area = area;
+    assert_eq!(
+        session.dispatch(Action::Capture).unwrap(),
+        vec![Effect::ImageChanged]
+    );
+    assert!(session.view_image);
+    assert!(session.frozen.is_none());
+}
+
+#[test]
+fn lists_the_screens_of_the_backend() {
+    let session = session();
+    assert_eq!(session.screenshots.total_screens(), 2);
+    assert_eq!(session.screenshots.get_screen().width, 200);
+}
+
+#[test]
+fn no_screens_is_an_error() {
+    assert!(Screenshots::with_backend(Box::new(MockBackend::new())).is_err());
+}
+
+#[test]
+fn captures_the_selected_screen() {
+    let mut session = session();
+    session.screenshots.screen_number = 1;
+    capture(&mut session);
+
+    // The image has the physical size of the screen
+    let image = session.image.as_ref().unwrap();
+    assert_eq!(image.dimensions(), (200, 100));
+    assert_eq!(*image, synthetic_image(200, 100, 1));
+}
+
+#[test]
+fn captures_an_area() {
+    let mut session = session();
+    capture_area(
+        &mut session,
+        Rectangle {
+            x: 10.0,
+            y: 20.0,
+            width: 30.0,
+            height: 40.0,
+        },
+    );
+
+    let image = session.image.as_ref().unwrap();
+    let screen = synthetic_image(200, 100, 0);
+    assert_eq!(image.dimensions(), (30, 40));
+    assert_eq!(image.get_pixel(0, 0), screen.get_pixel(10, 20));
+    assert_eq!(image.get_pixel(29, 39), screen.get_pixel(39, 59));
+}
+
+#[test]
+fn capture_annotate_and_save() {
+    let dir = output_dir();
+    let mut session = session();
+    capture(&mut session);
+
+    let red = Color::rgb(255, 0, 0);
+    session.add_annotation(Shape::Rect {
+        start: Point::new(20.0, 20.0),
+        end: Point::new(80.0, 60.0),
+        stroke: Stroke::new(4.0, red),
+        fill: Color::TRANSPARENT,
+        rounding: 0.0,
+    });
+    let path = session.save_to(&dir.path("annotated")).unwrap();
+    assert_eq!(path.extension().unwrap(), "png");
+
+    // The annotations are drawn on the saved image, the screenshot stays editable
+    let saved = image::open(&path).unwrap().to_rgba8();
+    assert_eq!(saved, session.rendered_image().unwrap());
+    assert_eq!(
+        *session.image.as_ref().unwrap(),
+        synthetic_image(200, 100, 0)
+    );
+    assert_eq!(session.annotations.len(), 1);
+    // On the border of the rectangle
+    assert_eq!(saved.get_pixel(20, 40).0, [255, 0, 0, 255]);
+    // Inside the rectangle the screenshot is untouched
+    assert_eq!(
+        saved.get_pixel(50, 40),
+        synthetic_image(200, 100, 0).get_pixel(50, 40)
+    );
+}
+
+#[test]
+fn new_screenshot_discards_the_annotations() {
+    let mut session = session();
+    capture(&mut session);
+    session.add_annotation(Shape::Line {
+        start: Point::new(0.0, 0.0),
+        end: Point::new(10.0, 10.0),
+        stroke: Stroke::new(1.0, Color::BLACK),
+        dash: Dash::Solid,
+    });
+    session.dispatch(Action::NewScreenshot).unwrap();
+    assert!(session.annotations.is_empty());
+    assert!(!session.view_image);
+}
+
+#[test]
+fn saves_in_the_format_of_the_extension() {
+    let dir = output_dir();
+    let mut session = session();
+    capture(&mut session);
+    let path = session.save_to(&dir.path("capture.jpg")).unwrap();
+    let format = image::io::Reader::open(&path)
+        .unwrap()
+        .with_guessed_format()
+        .unwrap()
+        .format();
+    assert_eq!(format, Some(image::ImageFormat::Jpeg));
+}
+
+#[test]
+fn mock_serves_image_files() {
+    let dir = output_dir();
+    let path = dir.path("screen.png");
+    synthetic_image(64, 32, 3).save(&path).unwrap();
+    let backend = MockBackend::from_files(&[path.clone(), path]).unwrap();
+    let mut screenshots = Screenshots::with_backend(Box::new(backend)).unwrap();
+    assert_eq!(screenshots.total_screens(), 2);
+
+    screenshots.screen_number = 1;
+    assert_eq!(screenshots.get_screen().x, 64);
+    assert_eq!(
+        screenshots.capture(None).unwrap(),
+        synthetic_image(64, 32, 3)
+    );
+}
+
+#[test]
+fn failed_capture_reports_the_area() {
+    let dir = output_dir();
+    let mut session = session();
+    session.dispatch(Action::SelectArea).unwrap();
+    session.dispatch(Action::Capture).unwrap();
+    session.capture().unwrap();
+    session.area = Rectangle {
+        x: 500.0,
+        y: 500.0,
+        width: 10.0,
+        height: 10.0,
+    };
+    match session.dispatch(Action::Capture) {
+        Err(AppError::Capture { screen, area, .. }) => {
+            assert_eq!(screen, 0);
+            assert_eq!(area, Some(session.area));
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+    // Another area can still be selected and there is nothing to save
+    assert!(session.selecting_area);
+    assert!(session.frozen.is_some());
+    assert!(matches!(
+        session.save_to(&dir.path("nothing.png")),
+        Err(AppError::NoScreenshot)
+    ));
+}
+
+#[test]
+fn failed_save_reports_the_path() {
+    let dir = output_dir();
+    let mut session = session();
+    capture(&mut session);
+    let path = dir.path("capture.unknown");
+    match session.save_to(&path) {
+        Err(AppError::Encode { path: failed, .. }) => assert_eq!(failed, path),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn captures_all_the_screens_in_one_image() {
+    let mut session = session();
+    session.dispatch(Action::SelectAllScreens).unwrap();
+    assert_eq!(session.selection_mode, Selection::AllScreens);
+    capture(&mut session);
+
+    // The second screen has twice the pixels per point, so the first one is enlarged
+    let image = session.image.as_ref().unwrap();
+    assert_eq!(image.dimensions(), (600, 200));
+    let second = synthetic_image(200, 100, 1);
+    assert_eq!(image.get_pixel(410, 20), second.get_pixel(10, 20));
+    assert_eq!(image.get_pixel(599, 99), second.get_pixel(199, 99));
+    // Nothing is under the second screen
+    assert_eq!(image.get_pixel(450, 150).0[3], 0);
+    assert_eq!(image.get_pixel(10, 150).0[3], 255);
+}
+
+#[test]
+fn screens_are_placed_by_their_position_on_the_desktop() {
+    let left = synthetic_image(100, 50, 0);
+    let right = synthetic_image(50, 50, 1);
+    let image = composite(&[
+        (screen_info(0, 0, 30, 50, 50, 1.0), right.clone()),
+        (screen_info(1, -100, 0, 100, 50, 1.0), left.clone()),
+    ]);
+    assert_eq!(image.dimensions(), (150, 80));
+    assert_eq!(image.get_pixel(0, 0), left.get_pixel(0, 0));
+    assert_eq!(image.get_pixel(100, 30), right.get_pixel(0, 0));
+    assert_eq!(image.get_pixel(149, 79), right.get_pixel(49, 49));
+    assert_eq!(image.get_pixel(120, 10).0[3], 0);
+    assert_eq!(image.get_pixel(50, 60).0[3], 0);
+}
+
+#[test]
+fn all_screens_is_a_setting() {
+    let mut saved = session();
+    saved.screenshots.all_screens = true;
+    let config = saved.config();
+    assert!(config.all_screens);
+
+    let mut restored = session();
+    restored.apply_config(config);
+    assert!(restored.screenshots.all_screens);
+    capture(&mut restored);
+    assert_eq!(restored.image.as_ref().unwrap().dimensions(), (600, 200));
+}
+
+#[test]
+fn captures_an_area_of_another_screen() {
+    let mut session = session();
+    // On the desktop, in points, the second screen starts at x = 200
+    capture_area(
+        &mut session,
+        Rectangle {
+            x: 210.0,
+            y: 10.0,
+            width: 30.0,
+            height: 20.0,
+        },
+    );
+
+    let image = session.image.as_ref().unwrap();
+    let screen = synthetic_image(200, 100, 1);
+    assert_eq!(image.dimensions(), (60, 40));
+    assert_eq!(image.get_pixel(0, 0), screen.get_pixel(20, 20));
+    assert_eq!(image.get_pixel(59, 39), screen.get_pixel(79, 59));
+}
+
+#[test]
+fn captures_an_area_across_two_screens() {
+    let mut session = session();
+    capture_area(
+        &mut session,
+        Rectangle {
+            x: 190.0,
+            y: 10.0,
+            width: 20.0,
+            height: 20.0,
+        },
+    );
+
+    // Both halves have the pixels of the second screen, which has the larger scale factor
+    let image = session.image.as_ref().unwrap();
+    let second = synthetic_image(200, 100, 1);
+    assert_eq!(image.dimensions(), (40, 40));
+    assert_eq!(image.get_pixel(20, 0), second.get_pixel(0, 20));
+    assert_eq!(image.get_pixel(39, 39), second.get_pixel(19, 59));
+    assert_eq!(image.get_pixel(0, 0).0[2], 0);
+    assert_eq!(image.get_pixel(0, 0).0[3], 255);
+}
+
+#[test]
+fn the_size_of_the_area_is_known_before_it_is_cut() {
+    let mut session = session();
+    session.dispatch(Action::SelectArea).unwrap();
+    session.dispatch(Action::Capture).unwrap();
+    session.capture().unwrap();
+    let frozen = session.frozen.as_ref().unwrap();
+    let across = Rectangle {
+        x: 190.0,
+        y: 10.0,
+        width: 20.0,
+        height: 20.0,
+    };
+    assert_eq!(frozen.size(across), Some((40, 40)));
+    assert_eq!(frozen.size(Rectangle { x: 500.0, ..across }), None);
+    // Going back drops the frozen screens
+    session.dispatch(Action::HomePage).unwrap();
+    assert!(!session.selecting_area);
+    assert!(session.frozen.is_none());
+}
+
+#[test]
+fn the_timer_is_managed_only_once_started() {
+    let mut session = session();
+    assert_eq!(session.dispatch(Action::ManageTimer).unwrap(), vec![]);
+    assert!(!session.view_image);
+}
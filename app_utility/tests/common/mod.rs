@@ -0,0 +1,72 @@
+// Helpers shared by the integration tests, each test binary uses only some of them
+#![allow(dead_code)]
+
+use app_utility::core::{
+    actions::Action,
+    annotation::{Annotations, Shape},
+    capture::Screenshots,
+    mock::{screen_info, MockBackend},
+    rasterizer,
+    session::Session,
+};
+use image::{Rgba, RgbaImage};
+use std::{
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+// A directory of its own for the files written by a test, removed with them when dropped
+pub struct OutputDir(PathBuf);
+
+impl OutputDir {
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.0.join(name)
+    }
+}
+
+impl Drop for OutputDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+pub fn output_dir() -> OutputDir {
+    // The tests of a binary run at the same time, each one gets a different directory
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let dir = std::env::temp_dir().join(format!(
+        "app_utility_{}_{}",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    OutputDir(dir)
+}
+
+// A session on a single screen of 200x100 pixels, before any screenshot is taken
+pub fn uncaptured_session() -> Session {
+    let backend = MockBackend::synthetic(&[screen_info(0, 0, 0, 200, 100, 1.0)]);
+    Session::new(Screenshots::with_backend(Box::new(backend)).unwrap())
+}
+
+// A session with the screenshot of its screen
+pub fn session() -> Session {
+    let mut session = uncaptured_session();
+    session.dispatch(Action::Capture).unwrap();
+    session.capture().unwrap();
+    session
+}
+
+// Draws a single annotation on the image, like the export
+pub fn burn(image: &mut RgbaImage, shape: Shape) {
+    let mut annotations = Annotations::default();
+    let annotation = annotations.create(shape);
+    annotations.insert(0, annotation);
+    rasterizer::burn_annotations(image, &annotations);
+}
+
+// A single annotation drawn on a white image of `width` x `height` pixels
+pub fn render(shape: Shape, width: u32, height: u32) -> RgbaImage {
+    let mut image = RgbaImage::from_pixel(width, height, Rgba([255, 255, 255, 255]));
+    burn(&mut image, shape);
+    image
+}
@@ -0,0 +1,95 @@
+use app_utility::core::{
+    annotation::{Color, Point, Shape, Stroke},
+    capture::Rectangle,
+    crop::{self, AspectRatio},
+};
+
+mod common;
+
+use common::session;
+
+fn area(x: f32, y: f32, width: f32, height: f32) -> Rectangle {
+    Rectangle {
+        x,
+        y,
+        width,
+        height,
+    }
+}
+
+#[test]
+fn crop_again_from_the_original() {
+    let mut session = session();
+    let original = session.original.clone().unwrap();
+    session.crop(area(50.0, 20.0, 40.0, 30.0));
+    session.crop(area(10.0, 10.0, 150.0, 80.0));
+
+    let image = session.image.as_ref().unwrap();
+    assert_eq!(image.dimensions(), (150, 80));
+    assert_eq!(image.get_pixel(0, 0), original.get_pixel(10, 10));
+    assert_eq!(session.crop, Some(area(10.0, 10.0, 150.0, 80.0)));
+}
+
+#[test]
+fn annotations_stay_on_the_same_pixels() {
+    let mut session = session();
+    session.add_annotation(Shape::Rect {
+        start: Point::new(60.0, 30.0),
+        end: Point::new(80.0, 40.0),
+        stroke: Stroke::new(2.0, Color::BLACK),
+        fill: Color::TRANSPARENT,
+        rounding: 0.0,
+    });
+    session.crop(area(50.0, 20.0, 100.0, 50.0));
+    session.crop(area(40.0, 0.0, 100.0, 50.0));
+
+    let shape = session.annotations.shapes().next().unwrap().clone();
+    assert_eq!(
+        shape,
+        Shape::Rect {
+            start: Point::new(20.0, 30.0),
+            end: Point::new(40.0, 40.0),
+            stroke: Stroke::new(2.0, Color::BLACK),
+            fill: Color::TRANSPARENT,
+            rounding: 0.0,
+        }
+    );
+}
+
+#[test]
+fn crop_of_the_whole_image_keeps_it_uncropped() {
+    let mut session = session();
+    session.crop(area(50.0, 20.0, 40.0, 30.0));
+    session.crop(area(-10.0, -10.0, 300.0, 300.0));
+
+    assert_eq!(session.crop, None);
+    assert_eq!(session.image.as_ref().unwrap().dimensions(), (200, 100));
+    assert!(session.crop(area(0.0, 0.0, 200.0, 100.0)).is_empty());
+}
+
+#[test]
+fn aspect_ratio_is_kept_inside_the_image() {
+    let dragged = crop::drag_corner(
+        Point::new(100.0, 50.0),
+        Point::new(300.0, 60.0),
+        AspectRatio::Square,
+        200,
+        100,
+    );
+    assert_eq!(dragged, area(100.0, 50.0, 50.0, 50.0));
+
+    let fitted = crop::fit(area(0.0, 0.0, 200.0, 100.0), AspectRatio::Standard);
+    assert!((fitted.height - 100.0).abs() < 1e-3);
+    assert!((fitted.width / fitted.height - 4.0 / 3.0).abs() < 1e-4);
+    assert_eq!(fitted.x, (200.0 - fitted.width) / 2.0);
+
+    let wider = crop::with_width(
+        area(0.0, 0.0, 40.0, 40.0),
+        80.0,
+        AspectRatio::Widescreen,
+        200,
+        100,
+    );
+    assert_eq!(wider.width, 80.0);
+    assert!((wider.height - 45.0).abs() < 1e-3);
+}
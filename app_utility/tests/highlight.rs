@@ -0,0 +1,90 @@
+use app_utility::core::{
+    annotation::{Annotations, Color, Point, Shape, Stroke},
+    mock::synthetic_image,
+    rasterizer,
+};
+use image::{Rgba, RgbaImage};
+
+mod common;
+
+use common::burn;
+
+const YELLOW: Color = Color::rgb(255, 230, 0);
+
+fn highlight(points: &[(f32, f32)], color: Color) -> Shape {
+    Shape::Highlight {
+        points: points.iter().map(|(x, y)| Point::new(*x, *y)).collect(),
+        stroke: Stroke::new(10.0, color),
+    }
+}
+
+#[test]
+fn multiplies_the_colors_under_it() {
+    let mut image = RgbaImage::from_pixel(60, 20, Rgba([255, 255, 255, 255]));
+    for y in 0..20 {
+        for x in 30..60 {
+            image.put_pixel(x, y, Rgba([0, 0, 0, 255]));
+        }
+    }
+    burn(&mut image, highlight(&[(5.0, 10.0), (55.0, 10.0)], YELLOW));
+
+    assert_eq!(image.get_pixel(15, 10).0, [255, 230, 0, 255]);
+    assert_eq!(image.get_pixel(45, 10).0, [0, 0, 0, 255]);
+    assert_eq!(image.get_pixel(15, 1).0, [255, 255, 255, 255]);
+}
+
+#[test]
+fn does_not_darken_where_the_stroke_overlaps_itself() {
+    let translucent = Color::rgba(255, 90, 200, 200);
+    let mut once = synthetic_image(80, 40, 1);
+    let mut back_and_forth = once.clone();
+    burn(
+        &mut once,
+        highlight(&[(10.0, 20.0), (70.0, 20.0)], translucent),
+    );
+    burn(
+        &mut back_and_forth,
+        highlight(
+            &[(10.0, 20.0), (70.0, 20.0), (10.0, 20.0), (70.0, 20.0)],
+            translucent,
+        ),
+    );
+
+    assert_eq!(once, back_and_forth);
+}
+
+#[test]
+fn preview_has_the_exported_pixels() {
+    let shape = highlight(&[(10.0, 10.0), (40.0, 30.0), (70.0, 12.0)], YELLOW);
+    let image = synthetic_image(80, 40, 2);
+    let mut exported = image.clone();
+    burn(&mut exported, shape.clone());
+
+    let (x, y, preview) = rasterizer::rendered(&image, &shape).unwrap();
+    for (px, py, pixel) in preview.enumerate_pixels() {
+        assert_eq!(pixel, exported.get_pixel(x + px, y + py));
+    }
+}
+
+#[test]
+fn preview_is_over_the_annotations_below() {
+    let image = synthetic_image(80, 40, 2);
+    let mut below = Annotations::default();
+    let rect = below.create(Shape::Rect {
+        start: Point::new(20.0, 5.0),
+        end: Point::new(60.0, 35.0),
+        stroke: Stroke::new(4.0, Color::rgb(0, 0, 200)),
+        fill: Color::rgb(0, 160, 0),
+        rounding: 0.0,
+    });
+    below.insert(0, rect);
+    let shape = highlight(&[(10.0, 20.0), (70.0, 20.0)], YELLOW);
+    let mut exported = image.clone();
+    rasterizer::burn_annotations(&mut exported, &below);
+    burn(&mut exported, shape.clone());
+
+    let (x, y, preview) = rasterizer::rendered_over(&image, &below, &shape).unwrap();
+    for (px, py, pixel) in preview.enumerate_pixels() {
+        assert_eq!(pixel, exported.get_pixel(x + px, y + py));
+    }
+}
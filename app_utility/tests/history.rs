@@ -0,0 +1,146 @@
+use app_utility::core::{
+    actions::Action,
+    annotation::{Color, Dash, Point, Shape, Stroke},
+    capture::Rectangle,
+    mock::synthetic_image,
+    session::{Effect, Session},
+};
+
+mod common;
+
+use common::session;
+
+fn line(x: f32) -> Shape {
+    Shape::Line {
+        start: Point::new(x, 10.0),
+        end: Point::new(x, 50.0),
+        stroke: Stroke::new(2.0, Color::BLACK),
+        dash: Dash::Solid,
+    }
+}
+
+fn shapes(session: &Session) -> Vec<Shape> {
+    session.annotations.shapes().cloned().collect()
+}
+
+#[test]
+fn undo_and_redo_additions() {
+    let mut session = session();
+    session.add_annotation(line(10.0));
+    session.add_annotation(line(20.0));
+
+    session.dispatch(Action::Undo).unwrap();
+    assert_eq!(shapes(&session), vec![line(10.0)]);
+    session.dispatch(Action::Undo).unwrap();
+    assert!(session.annotations.is_empty());
+    assert!(!session.history.can_undo());
+
+    session.dispatch(Action::Redo).unwrap();
+    session.dispatch(Action::Redo).unwrap();
+    assert_eq!(shapes(&session), vec![line(10.0), line(20.0)]);
+    assert!(!session.history.can_redo());
+}
+
+#[test]
+fn new_change_discards_the_redo() {
+    let mut session = session();
+    session.add_annotation(line(10.0));
+    session.dispatch(Action::Undo).unwrap();
+    session.add_annotation(line(20.0));
+    assert!(!session.history.can_redo());
+    assert_eq!(shapes(&session), vec![line(20.0)]);
+}
+
+#[test]
+fn deleted_annotation_comes_back_in_its_place() {
+    let mut session = session();
+    session.add_annotation(line(10.0));
+    let id = session.add_annotation(line(20.0));
+    session.add_annotation(line(30.0));
+
+    session.delete_annotation(id);
+    assert_eq!(shapes(&session), vec![line(10.0), line(30.0)]);
+    session.dispatch(Action::Undo).unwrap();
+    assert_eq!(shapes(&session), vec![line(10.0), line(20.0), line(30.0)]);
+}
+
+#[test]
+fn undo_move_and_restyle() {
+    let mut session = session();
+    let id = session.add_annotation(line(10.0));
+    session.move_annotation(id, Point::new(5.0, 0.0));
+    let red = Stroke::new(6.0, Color::rgb(255, 0, 0));
+    session.restyle_annotation(id, red);
+    assert_eq!(session.annotations.get(id).unwrap().stroke(), red);
+
+    session.dispatch(Action::Undo).unwrap();
+    assert_eq!(shapes(&session), vec![line(15.0)]);
+    session.dispatch(Action::Undo).unwrap();
+    assert_eq!(shapes(&session), vec![line(10.0)]);
+}
+
+#[test]
+fn restyles_are_merged_only_in_one_edit() {
+    let mut session = session();
+    let id = session.add_annotation(line(10.0));
+    let red = |width| Stroke::new(width, Color::rgb(255, 0, 0));
+    session.restyle_annotation(id, red(3.0));
+    session.move_annotation(id, Point::new(5.0, 0.0));
+    session.dispatch(Action::Undo).unwrap();
+    session.restyle_annotation(id, red(4.0));
+    session.history.end_edit();
+    session.restyle_annotation(id, red(5.0));
+
+    session.dispatch(Action::Undo).unwrap();
+    assert_eq!(session.annotations.get(id).unwrap().stroke(), red(4.0));
+    session.dispatch(Action::Undo).unwrap();
+    assert_eq!(session.annotations.get(id).unwrap().stroke(), red(3.0));
+    session.dispatch(Action::Undo).unwrap();
+    assert_eq!(shapes(&session), vec![line(10.0)]);
+}
+
+#[test]
+fn undo_crop() {
+    let mut session = session();
+    session.add_annotation(line(50.0));
+    let effects = session.crop(Rectangle {
+        x: 40.0,
+        y: 5.0,
+        width: 100.0,
+        height: 60.0,
+    });
+    assert_eq!(effects, vec![Effect::ImageChanged]);
+    assert_eq!(session.image.as_ref().unwrap().dimensions(), (100, 60));
+    // The annotations stay on the same pixels
+    assert_eq!(
+        shapes(&session),
+        vec![Shape::Line {
+            start: Point::new(10.0, 5.0),
+            end: Point::new(10.0, 45.0),
+            stroke: Stroke::new(2.0, Color::BLACK),
+            dash: Dash::Solid,
+        }]
+    );
+
+    assert_eq!(
+        session.dispatch(Action::Undo).unwrap(),
+        vec![Effect::ImageChanged]
+    );
+    assert_eq!(
+        *session.image.as_ref().unwrap(),
+        synthetic_image(200, 100, 0)
+    );
+    assert_eq!(shapes(&session), vec![line(50.0)]);
+}
+
+#[test]
+fn history_is_cleared_only_by_a_new_screenshot() {
+    let mut session = session();
+    session.add_annotation(line(10.0));
+    session.dispatch(Action::Modify).unwrap();
+    assert!(session.history.can_undo());
+
+    session.dispatch(Action::NewScreenshot).unwrap();
+    assert!(!session.history.can_undo());
+    assert!(session.annotations.is_empty());
+}
@@ -0,0 +1,130 @@
+use app_utility::core::{
+    actions::Action,
+    annotation::{Color, MarkerSequence, Numbering, Point, Shape},
+    session::Session,
+};
+
+mod common;
+
+use common::session;
+
+fn marker(x: f32) -> Shape {
+    Shape::Marker {
+        center: Point::new(x, 50.0),
+        radius: 12.0,
+        color: Color::rgb(220, 30, 30),
+    }
+}
+
+fn labels(session: &Session) -> Vec<String> {
+    session
+        .annotations
+        .items()
+        .iter()
+        .filter_map(|annotation| session.annotations.marker_label(annotation.id))
+        .collect()
+}
+
+#[test]
+fn markers_are_renumbered_when_one_is_removed() {
+    let mut session = session();
+    let first = session.add_annotation(marker(20.0));
+    session.add_annotation(marker(60.0));
+    session.add_annotation(marker(100.0));
+    assert_eq!(labels(&session), ["1", "2", "3"]);
+    assert_eq!(session.annotations.next_marker_label(), "4");
+
+    session.delete_annotation(first);
+    assert_eq!(labels(&session), ["1", "2"]);
+
+    session.dispatch(Action::Undo).unwrap();
+    assert_eq!(labels(&session), ["1", "2", "3"]);
+    assert_eq!(session.annotations.marker_label(first).unwrap(), "1");
+
+    session.dispatch(Action::Undo).unwrap();
+    assert_eq!(labels(&session), ["1", "2"]);
+}
+
+#[test]
+fn letters_and_custom_start() {
+    let numbering = Numbering {
+        sequence: MarkerSequence::Letters,
+        start: 1,
+    };
+    let letters: Vec<String> = [0, 1, 25, 26, 27, 701, 702]
+        .iter()
+        .map(|index| numbering.label(*index))
+        .collect();
+    assert_eq!(letters, ["A", "B", "Z", "AA", "AB", "ZZ", "AAA"]);
+
+    let mut session = session();
+    session.renumber(Numbering {
+        sequence: MarkerSequence::Numbers,
+        start: 5,
+    });
+    session.add_annotation(marker(20.0));
+    session.add_annotation(marker(60.0));
+    assert_eq!(labels(&session), ["5", "6"]);
+    session.renumber(Numbering {
+        sequence: MarkerSequence::Letters,
+        start: 5,
+    });
+    assert_eq!(labels(&session), ["E", "F"]);
+}
+
+#[test]
+fn only_the_numbers_start_at_zero() {
+    let mut session = session();
+    session.add_annotation(marker(20.0));
+    session.add_annotation(marker(60.0));
+    let numbers = Numbering {
+        sequence: MarkerSequence::Numbers,
+        start: 0,
+    };
+    session.renumber(numbers);
+    assert_eq!(labels(&session), ["0", "1"]);
+
+    // There is no letter for 0
+    let letters = Numbering {
+        sequence: MarkerSequence::Letters,
+        start: 0,
+    };
+    assert!(!letters.is_valid());
+    session.renumber(letters);
+    assert_eq!(session.annotations.numbering, numbers);
+}
+
+#[test]
+fn renumbering_can_be_undone() {
+    let mut session = session();
+    session.add_annotation(marker(20.0));
+    // e.g. while the start is dragged
+    for start in 2..=4 {
+        session.renumber(Numbering {
+            sequence: MarkerSequence::Letters,
+            start,
+        });
+    }
+    assert_eq!(labels(&session), ["D"]);
+
+    session.dispatch(Action::Undo).unwrap();
+    assert_eq!(session.annotations.numbering, Numbering::default());
+    assert_eq!(labels(&session), ["1"]);
+    session.dispatch(Action::Redo).unwrap();
+    assert_eq!(labels(&session), ["D"]);
+}
+
+#[test]
+fn markers_are_rendered_with_their_label() {
+    let mut session = session();
+    session.add_annotation(marker(60.0));
+    let image = session.rendered_image().unwrap();
+
+    // The badge is filled, with a light label in the middle
+    assert_eq!(image.get_pixel(60 - 10, 50).0, [220, 30, 30, 255]);
+    let label = (52..68)
+        .flat_map(|x| (42..58).map(move |y| (x, y)))
+        .filter(|(x, y)| image.get_pixel(*x, *y).0[1] > 150)
+        .count();
+    assert!(label > 5);
+}
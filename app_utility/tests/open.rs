@@ -0,0 +1,83 @@
+use app_utility::core::{
+    actions::Action,
+    annotation::{Color, Point, Shape, Stroke},
+    error::AppError,
+    export::{self, ExportSettings},
+    mock::synthetic_image,
+    session::Effect,
+    shortcut::{AllShortcuts, KeyboardShortcut},
+};
+
+mod common;
+
+use common::{output_dir, session, uncaptured_session};
+
+#[test]
+fn open_asks_for_a_file() {
+    let mut session = uncaptured_session();
+    let effects = session.dispatch(Action::Open).unwrap();
+    assert!(matches!(
+        effects.as_slice(),
+        [Effect::ChooseOpenPath { .. }]
+    ));
+    let shortcuts = AllShortcuts::default();
+    let open = shortcuts
+        .vec
+        .iter()
+        .find(|shortcut| shortcut.action == Action::Open)
+        .unwrap();
+    assert_eq!(open.shortcut, KeyboardShortcut::command("O"));
+    assert!(!shortcuts.has_duplicate_shortcuts());
+}
+
+#[test]
+fn opened_images_are_edited_like_screenshots() {
+    let dir = output_dir();
+    let mut session = session();
+    session.add_annotation(Shape::Circle {
+        center: Point::new(50.0, 50.0),
+        radius: 10.0,
+        stroke: Stroke::new(2.0, Color::BLACK),
+        fill: Color::TRANSPARENT,
+    });
+
+    for (name, image) in [
+        ("opened.png", synthetic_image(120, 80, 3)),
+        ("opened.bmp", synthetic_image(64, 48, 5)),
+    ] {
+        let path = dir.path(name);
+        export::save(&image, &path, &ExportSettings::default()).unwrap();
+        assert_eq!(session.open(&path).unwrap(), vec![Effect::ImageChanged]);
+
+        assert!(session.view_image);
+        assert!(session.annotations.is_empty());
+        assert!(!session.history.can_undo());
+        assert_eq!(session.image.as_ref(), Some(&image));
+        assert_eq!(session.original.as_ref(), Some(&image));
+    }
+
+    // Saving works as for a screenshot
+    session.add_annotation(Shape::Rect {
+        start: Point::new(0.0, 0.0),
+        end: Point::new(20.0, 20.0),
+        stroke: Stroke::new(4.0, Color::rgb(255, 0, 0)),
+        fill: Color::TRANSPARENT,
+        rounding: 0.0,
+    });
+    let saved = session.save_to(&dir.path("annotated.png")).unwrap();
+    let saved = image::open(saved).unwrap().to_rgba8();
+    assert_eq!(saved.dimensions(), (64, 48));
+    assert_eq!(saved.get_pixel(0, 10).0, [255, 0, 0, 255]);
+}
+
+#[test]
+fn unreadable_images_are_reported() {
+    let dir = output_dir();
+    let path = dir.path("garbage.png");
+    std::fs::write(&path, b"definitely not a png").unwrap();
+    let mut session = uncaptured_session();
+    let err = session.open(&path).unwrap_err();
+    assert!(matches!(err, AppError::Open { .. }));
+    assert!(err.to_string().contains("garbage.png"));
+    assert!(!session.view_image);
+}
@@ -0,0 +1,161 @@
+use app_utility::core::{
+    annotation::{
+        ArrowStyle, Color, Dash, MarkerSequence, Numbering, Point, Redaction, Shape, Stroke,
+    },
+    capture::Rectangle,
+    error::AppError,
+    session::{Effect, Session},
+    text::TextStyle,
+};
+use std::io::Write;
+
+mod common;
+
+use common::{output_dir, session, uncaptured_session};
+
+fn shapes(session: &Session) -> Vec<Shape> {
+    session.annotations.shapes().cloned().collect()
+}
+
+#[test]
+fn reopened_projects_are_still_editable() {
+    let dir = output_dir();
+    let mut session = session();
+    session.crop(Rectangle {
+        x: 10.0,
+        y: 10.0,
+        width: 150.0,
+        height: 80.0,
+    });
+    session.annotations.numbering = Numbering {
+        sequence: MarkerSequence::Letters,
+        start: 3,
+    };
+    session.add_annotation(Shape::Arrow {
+        start: Point::new(5.0, 5.0),
+        end: Point::new(100.0, 60.0),
+        control: Some(Point::new(80.0, 10.0)),
+        stroke: Stroke::new(3.0, Color::rgb(200, 0, 0)),
+        dash: Dash::Dashed,
+        style: ArrowStyle::default(),
+    });
+    session.add_annotation(Shape::Text {
+        pos: Point::new(20.0, 20.0),
+        text: "Two\nlines".to_string(),
+        style: TextStyle {
+            bold: true,
+            ..TextStyle::default()
+        },
+    });
+    session.add_annotation(Shape::Redact {
+        start: Point::new(0.0, 0.0),
+        end: Point::new(30.0, 30.0),
+        redaction: Redaction::Pixelate(6),
+    });
+    session.add_annotation(Shape::Marker {
+        center: Point::new(120.0, 40.0),
+        radius: 12.0,
+        color: Color::rgba(0, 90, 200, 180),
+    });
+
+    let path = session.save_to(&dir.path("edit.shotproj")).unwrap();
+    let mut reopened = uncaptured_session();
+    assert_eq!(reopened.open(&path).unwrap(), vec![Effect::ImageChanged]);
+
+    assert!(reopened.view_image);
+    assert_eq!(shapes(&reopened), shapes(&session));
+    assert_eq!(
+        reopened.annotations.numbering,
+        session.annotations.numbering
+    );
+    assert_eq!(reopened.crop, session.crop);
+    assert_eq!(reopened.original, session.original);
+    assert_eq!(reopened.rendered_image(), session.rendered_image());
+    assert!(!reopened.history.can_undo());
+
+    // The annotations can be changed, and the crop too since the original is kept
+    let id = reopened.annotations.items()[1].id;
+    reopened.delete_annotation(id);
+    assert_eq!(reopened.annotations.len(), 3);
+    reopened.crop(Rectangle {
+        x: 0.0,
+        y: 0.0,
+        width: 200.0,
+        height: 100.0,
+    });
+    assert_eq!(reopened.image.unwrap().dimensions(), (200, 100));
+}
+
+#[test]
+fn other_files_are_not_projects() {
+    let dir = output_dir();
+    let path = dir.path("broken.shotproj");
+    std::fs::write(&path, b"not a zip").unwrap();
+    let mut session = uncaptured_session();
+    assert!(matches!(session.open(&path), Err(AppError::Open { .. })));
+    assert!(!session.view_image);
+
+    let missing = dir.path("missing.shotproj");
+    assert!(matches!(session.open(&missing), Err(AppError::Io { .. })));
+}
+
+#[test]
+fn newer_projects_are_rejected() {
+    let dir = output_dir();
+    let path = dir.path("newer.shotproj");
+    let mut archive = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+    archive
+        .start_file("annotations.json", zip::write::FileOptions::default())
+        .unwrap();
+    archive
+        .write_all(br#"{"version": 99, "layers": []}"#)
+        .unwrap();
+    archive.finish().unwrap();
+
+    let err = uncaptured_session().open(&path).unwrap_err();
+    assert!(err.to_string().contains("version 99"));
+}
+
+#[test]
+fn projects_keep_the_pixels_under_the_redactions() {
+    let dir = output_dir();
+    let mut session = session();
+    let path = dir.path("redacted.shotproj");
+    assert!(!session.reveals_redactions(&path));
+    session.add_annotation(Shape::Redact {
+        start: Point::new(0.0, 0.0),
+        end: Point::new(50.0, 50.0),
+        redaction: Redaction::Fill(Color::rgb(0, 0, 0)),
+    });
+    assert!(session.reveals_redactions(&path));
+    assert!(!session.reveals_redactions(&dir.path("redacted.png")));
+
+    session.save_to(&path).unwrap();
+    let mut reopened = uncaptured_session();
+    reopened.open(&path).unwrap();
+    assert_eq!(reopened.original, session.original);
+    assert_ne!(
+        reopened.original.unwrap().get_pixel(10, 10),
+        session.rendered_image().unwrap().get_pixel(10, 10)
+    );
+}
+
+#[test]
+fn letters_starting_at_zero_are_rejected() {
+    let dir = output_dir();
+    let path = dir.path("zero.shotproj");
+    let mut archive = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+    archive
+        .start_file("annotations.json", zip::write::FileOptions::default())
+        .unwrap();
+    archive
+        .write_all(
+            br#"{"version": 1, "crop": null, "numbering": {"sequence": "Letters", "start": 0}, "shapes": []}"#,
+        )
+        .unwrap();
+    archive.finish().unwrap();
+
+    let err = uncaptured_session().open(&path).unwrap_err();
+    assert!(matches!(err, AppError::Open { .. }));
+    assert!(err.to_string().contains("start at A"));
+}
@@ -0,0 +1,107 @@
+use app_utility::core::{
+    annotation::{Color, Point, Redaction, Shape},
+    mock::synthetic_image,
+};
+
+mod common;
+
+use common::{burn, output_dir, session};
+
+fn redact(start: Point, end: Point, redaction: Redaction) -> Shape {
+    Shape::Redact {
+        start,
+        end,
+        redaction,
+    }
+}
+
+#[test]
+fn fill_is_opaque_and_stays_in_the_area() {
+    let original = synthetic_image(100, 50, 0);
+    let mut image = original.clone();
+    burn(
+        &mut image,
+        redact(
+            Point::new(60.0, 40.0),
+            Point::new(10.0, 20.0),
+            Redaction::Fill(Color::rgba(255, 0, 0, 100)),
+        ),
+    );
+
+    for (x, y, pixel) in image.enumerate_pixels() {
+        if (10..60).contains(&x) && (20..40).contains(&y) {
+            assert_eq!(pixel.0, [255, 0, 0, 255]);
+        } else {
+            assert_eq!(pixel, original.get_pixel(x, y));
+        }
+    }
+}
+
+#[test]
+fn pixelate_makes_uniform_blocks() {
+    let original = synthetic_image(100, 50, 1);
+    let mut image = original.clone();
+    burn(
+        &mut image,
+        redact(
+            Point::new(0.0, 0.0),
+            Point::new(40.0, 30.0),
+            Redaction::Pixelate(10),
+        ),
+    );
+
+    for block_y in (0..30).step_by(10) {
+        for block_x in (0..40).step_by(10) {
+            let first = image.get_pixel(block_x, block_y);
+            for y in block_y..block_y + 10 {
+                for x in block_x..block_x + 10 {
+                    assert_eq!(image.get_pixel(x, y), first);
+                }
+            }
+        }
+    }
+    assert_ne!(image.get_pixel(0, 0), image.get_pixel(30, 20));
+    assert_eq!(image.get_pixel(40, 30), original.get_pixel(40, 30));
+}
+
+#[test]
+fn blur_changes_only_the_area() {
+    let mut original = synthetic_image(100, 50, 2);
+    // A sharp edge that the blur has to soften
+    for y in 0..50 {
+        for x in 50..100 {
+            original.put_pixel(x, y, image::Rgba([255, 255, 255, 255]));
+        }
+    }
+    let mut image = original.clone();
+    burn(
+        &mut image,
+        redact(
+            Point::new(40.0, 10.0),
+            Point::new(60.0, 40.0),
+            Redaction::Blur(4.0),
+        ),
+    );
+
+    assert_ne!(image.get_pixel(50, 25), original.get_pixel(50, 25));
+    assert_eq!(image.get_pixel(39, 25), original.get_pixel(39, 25));
+    assert_eq!(image.get_pixel(50, 40), original.get_pixel(50, 40));
+}
+
+#[test]
+fn saved_file_has_no_trace_of_the_redacted_pixels() {
+    let dir = output_dir();
+    let mut session = session();
+    session.add_annotation(Shape::Redact {
+        start: Point::new(20.0, 20.0),
+        end: Point::new(80.0, 60.0),
+        redaction: Redaction::Fill(Color::BLACK),
+    });
+
+    let path = session.save_to(&dir.path("redacted.png")).unwrap();
+    let saved = image::open(path).unwrap().to_rgba8();
+
+    assert!((20..80).all(|x| (20..60).all(|y| saved.get_pixel(x, y).0 == [0, 0, 0, 255])));
+    // The screenshot being edited still has the pixels, so the redaction can be changed
+    assert_ne!(session.image.unwrap().get_pixel(50, 40).0, [0, 0, 0, 255]);
+}
@@ -0,0 +1,171 @@
+use app_utility::core::{
+    actions::Action,
+    annotation::{Color, Point},
+    capture::{composite, Rectangle, Screenshots},
+    mock::{screen_info, synthetic_image, MockBackend},
+    session::Session,
+};
+
+const SCALES: [f32; 4] = [1.0, 1.25, 1.5, 2.0];
+
+// Not at the top left corner of the desktop, so that the position of the screen counts
+const SCREEN: Rectangle = Rectangle {
+    x: 100.0,
+    y: 50.0,
+    width: 160.0,
+    height: 100.0,
+};
+
+// An area of the screen whose edges are not on whole pixels at every scale
+const AREA: Rectangle = Rectangle {
+    x: 102.0,
+    y: 53.0,
+    width: 41.0,
+    height: 27.0,
+};
+
+fn rectangle(x: f32, y: f32, width: f32, height: f32) -> Rectangle {
+    Rectangle {
+        x,
+        y,
+        width,
+        height,
+    }
+}
+
+fn session(scale: f32) -> Session {
+    let backend = MockBackend::synthetic(&[screen_info(0, 100, 50, 160, 100, scale)]);
+    Session::new(Screenshots::with_backend(Box::new(backend)).unwrap())
+}
+
+#[test]
+fn areas_of_the_desktop_are_in_pixels_of_the_screen() {
+    let expected = [
+        rectangle(2.0, 3.0, 41.0, 27.0),
+        rectangle(3.0, 4.0, 51.0, 34.0),
+        rectangle(3.0, 5.0, 62.0, 40.0),
+        rectangle(4.0, 6.0, 82.0, 54.0),
+    ];
+    for (scale, expected) in SCALES.into_iter().zip(expected) {
+        let screen = session(scale).screenshots.get_screen();
+        assert_eq!(screen.to_physical(AREA), expected, "scale {}", scale);
+        assert_eq!(
+            screen.to_physical(SCREEN),
+            rectangle(0.0, 0.0, 160.0 * scale, 100.0 * scale),
+            "scale {}",
+            scale
+        );
+    }
+}
+
+#[test]
+fn an_area_is_captured_with_the_pixels_under_it() {
+    for scale in SCALES {
+        let mut session = session(scale);
+        let pixels = session.screenshots.get_screen().to_physical(AREA);
+        session.dispatch(Action::SelectArea).unwrap();
+        session.dispatch(Action::Capture).unwrap();
+        session.capture().unwrap();
+        session.area = AREA;
+        session.dispatch(Action::Capture).unwrap();
+
+        let image = session.image.as_ref().unwrap();
+        let screen = synthetic_image((160.0 * scale) as u32, (100.0 * scale) as u32, 0);
+        let (x, y) = (pixels.x as u32, pixels.y as u32);
+        let (width, height) = (pixels.width as u32, pixels.height as u32);
+        assert_eq!(image.dimensions(), (width, height), "scale {}", scale);
+        assert_eq!(image.get_pixel(0, 0), screen.get_pixel(x, y));
+        assert_eq!(
+            image.get_pixel(width - 1, height - 1),
+            screen.get_pixel(x + width - 1, y + height - 1)
+        );
+    }
+}
+
+#[test]
+fn areas_side_by_side_stay_side_by_side() {
+    for scale in SCALES {
+        let screen = session(scale).screenshots.get_screen();
+        let left = screen.to_physical(rectangle(100.0, 50.0, 33.3, 10.0));
+        let right = screen.to_physical(rectangle(133.3, 50.0, 20.7, 10.0));
+        let both = screen.to_physical(rectangle(100.0, 50.0, 54.0, 10.0));
+        assert_eq!(left.x + left.width, right.x, "scale {}", scale);
+        assert_eq!(left.width + right.width, both.width, "scale {}", scale);
+    }
+}
+
+#[test]
+fn screens_are_composed_at_the_largest_scale() {
+    for scale in SCALES {
+        let high = screen_info(0, 0, 0, 160, 100, scale);
+        let low = screen_info(1, 160, 0, 80, 100, 1.0);
+        let high_image = synthetic_image((160.0 * scale) as u32, (100.0 * scale) as u32, 0);
+        let image = composite(&[
+            (high, high_image.clone()),
+            (low, synthetic_image(80, 100, 1)),
+        ]);
+        assert_eq!(
+            image.dimensions(),
+            ((240.0 * scale).round() as u32, (100.0 * scale) as u32),
+            "scale {}",
+            scale
+        );
+        let last = high_image.width() - 1;
+        assert_eq!(image.get_pixel(last, 0), high_image.get_pixel(last, 0));
+        assert_eq!(image.get_pixel(last + 1, 0)[2], 64);
+    }
+}
+
+#[test]
+fn the_pixel_under_a_point_is_found() {
+    for scale in SCALES {
+        let frozen = session(scale).screenshots.freeze().unwrap();
+        let pixel = frozen.pixel_at(Point::new(110.5, 60.5)).unwrap();
+        let expected = (10.5 * scale) as u32;
+        assert_eq!((pixel.x, pixel.y), (expected, expected), "scale {}", scale);
+        // The screen starts at 100, 50 points on the desktop
+        assert_eq!(
+            pixel.absolute,
+            (
+                (100.0 * scale).round() as i32 + expected as i32,
+                (50.0 * scale).round() as i32 + expected as i32
+            )
+        );
+        let [r, g, b, a] = frozen.screens[0].1.get_pixel(expected, expected).0;
+        assert_eq!(pixel.color, Color::rgba(r, g, b, a));
+        assert_eq!(frozen.pixel_at(Point::new(99.0, 60.0)), None);
+    }
+}
+
+#[test]
+fn points_are_nudged_by_whole_pixels_of_the_screen() {
+    for scale in SCALES {
+        let frozen = session(scale).screenshots.freeze().unwrap();
+        let screen = frozen.screens[0].0;
+        let point = frozen.nudge(Point::new(110.3, 60.3), Point::default());
+        let pixels = screen.to_physical(Rectangle {
+            x: point.x,
+            y: point.y,
+            width: 0.0,
+            height: 0.0,
+        });
+        // On a boundary between pixels, the nearest one
+        assert!(
+            ((point.x - 100.0) * scale - pixels.x).abs() < 1e-3,
+            "scale {}",
+            scale
+        );
+        assert_eq!(pixels.x, (10.3 * scale).round());
+        let moved = frozen.nudge(point, Point::new(1.0, -10.0));
+        assert!(
+            (moved.x - point.x - 1.0 / scale).abs() < 1e-4,
+            "scale {}",
+            scale
+        );
+        assert!(
+            (moved.y - point.y + 10.0 / scale).abs() < 1e-4,
+            "scale {}",
+            scale
+        );
+    }
+}
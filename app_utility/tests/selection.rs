@@ -0,0 +1,130 @@
+use app_utility::core::{
+    actions::Action,
+    annotation::{Annotations, Color, Point, Shape, Stroke},
+    selection,
+};
+
+mod common;
+
+use common::uncaptured_session;
+
+fn stroke() -> Stroke {
+    Stroke::new(4.0, Color::BLACK)
+}
+
+fn rect() -> Shape {
+    Shape::Rect {
+        start: Point::new(10.0, 10.0),
+        end: Point::new(50.0, 30.0),
+        stroke: stroke(),
+        fill: Color::TRANSPARENT,
+        rounding: 0.0,
+    }
+}
+
+fn circle() -> Shape {
+    Shape::Circle {
+        center: Point::new(40.0, 20.0),
+        radius: 10.0,
+        stroke: stroke(),
+        fill: Color::TRANSPARENT,
+    }
+}
+
+#[test]
+fn hit_test_finds_the_topmost_stroke() {
+    let mut annotations = Annotations::default();
+    let rect = annotations.create(rect());
+    annotations.insert(0, rect.clone());
+    let circle = annotations.create(circle());
+    annotations.insert(1, circle.clone());
+
+    // On the border of the rectangle only
+    assert_eq!(
+        selection::hit_test(&annotations, Point::new(11.0, 20.0), 1.0),
+        Some(rect.id)
+    );
+    // Where the circle crosses the rectangle
+    assert_eq!(
+        selection::hit_test(&annotations, Point::new(40.0, 30.0), 1.0),
+        Some(circle.id)
+    );
+    // Inside the rectangle, away from the strokes
+    assert_eq!(
+        selection::hit_test(&annotations, Point::new(20.0, 20.0), 1.0),
+        None
+    );
+}
+
+#[test]
+fn resize_from_a_corner_keeps_the_opposite_one() {
+    let shape = rect();
+    let handles = selection::handles(&shape);
+    assert_eq!(handles.len(), 4);
+    let handle = selection::handle_at(&shape, Point::new(51.0, 31.0), 3.0).unwrap();
+    assert_eq!(handles[handle], Point::new(50.0, 30.0));
+
+    assert_eq!(
+        selection::resized(&shape, handle, Point::new(70.0, 60.0)),
+        Shape::Rect {
+            start: Point::new(10.0, 10.0),
+            end: Point::new(70.0, 60.0),
+            stroke: stroke(),
+            fill: Color::TRANSPARENT,
+            rounding: 0.0,
+        }
+    );
+}
+
+#[test]
+fn resize_stretches_a_pen_stroke() {
+    let shape = Shape::Pen {
+        points: vec![
+            Point::new(0.0, 0.0),
+            Point::new(5.0, 5.0),
+            Point::new(10.0, 0.0),
+        ],
+        stroke: stroke(),
+    };
+    // The bottom right corner, moved to double the size
+    let resized = selection::resized(&shape, 2, Point::new(20.0, 10.0));
+    assert_eq!(
+        resized,
+        Shape::Pen {
+            points: vec![
+                Point::new(0.0, 0.0),
+                Point::new(10.0, 10.0),
+                Point::new(20.0, 0.0)
+            ],
+            stroke: stroke(),
+        }
+    );
+}
+
+#[test]
+fn edits_of_the_selection_can_be_undone() {
+    let mut session = uncaptured_session();
+    let id = session.add_annotation(circle());
+
+    session.reshape_annotation(id, selection::resized(&circle(), 0, Point::new(60.0, 20.0)));
+    session.restyle_annotation(id, Stroke::new(2.0, Color::rgb(0, 0, 255)));
+    session.restyle_annotation(id, Stroke::new(3.0, Color::rgb(0, 0, 255)));
+    assert_eq!(
+        session.annotations.get(id).unwrap(),
+        &Shape::Circle {
+            center: Point::new(40.0, 20.0),
+            radius: 20.0,
+            stroke: Stroke::new(3.0, Color::rgb(0, 0, 255)),
+            fill: Color::TRANSPARENT,
+        }
+    );
+
+    // Both restyles are undone at once
+    session.dispatch(Action::Undo).unwrap();
+    assert_eq!(session.annotations.get(id).unwrap().stroke(), stroke());
+    session.dispatch(Action::Undo).unwrap();
+    assert_eq!(session.annotations.get(id).unwrap(), &circle());
+
+    session.delete_annotation(id);
+    assert!(session.annotations.get(id).is_none());
+}
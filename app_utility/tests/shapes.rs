@@ -0,0 +1,102 @@
+use app_utility::core::{
+    actions::Action,
+    annotation::{Annotations, Color, Point, Shape, Stroke},
+    selection,
+};
+
+mod common;
+
+use common::{render, session};
+
+const WHITE: [u8; 4] = [255, 255, 255, 255];
+const RED: Color = Color::rgb(255, 0, 0);
+
+fn rect(fill: Color, rounding: f32) -> Shape {
+    Shape::Rect {
+        start: Point::new(10.0, 10.0),
+        end: Point::new(90.0, 50.0),
+        stroke: Stroke::new(2.0, Color::BLACK),
+        fill,
+        rounding,
+    }
+}
+
+#[test]
+fn fill_blends_under_the_stroke() {
+    let image = render(rect(Color::rgba(255, 0, 0, 128), 0.0), 100, 60);
+
+    let inside = image.get_pixel(50, 30).0;
+    assert!(inside[0] == 255 && inside[1] < 200 && inside[1] > 50);
+    assert_eq!(image.get_pixel(10, 30).0, [0, 0, 0, 255]);
+    assert_eq!(image.get_pixel(5, 30).0, WHITE);
+}
+
+#[test]
+fn rounded_corners_are_left_out() {
+    let sharp = render(rect(RED, 0.0), 100, 60);
+    let rounded = render(rect(RED, 15.0), 100, 60);
+
+    assert_ne!(sharp.get_pixel(12, 12).0, WHITE);
+    assert_eq!(rounded.get_pixel(12, 12).0, WHITE);
+    assert_eq!(rounded.get_pixel(50, 30).0, [255, 0, 0, 255]);
+}
+
+#[test]
+fn ellipse_fits_the_drag_rectangle() {
+    let image = render(
+        Shape::Ellipse {
+            start: Point::new(90.0, 50.0),
+            end: Point::new(10.0, 10.0),
+            stroke: Stroke::new(2.0, Color::BLACK),
+            fill: RED,
+        },
+        100,
+        60,
+    );
+
+    // Touches the middle of each side but not the corners
+    assert!(image.get_pixel(10, 30).0[0] < 30);
+    assert!(image.get_pixel(50, 10).0[0] < 30);
+    assert_eq!(image.get_pixel(14, 14).0, WHITE);
+    assert_eq!(image.get_pixel(50, 30).0, [255, 0, 0, 255]);
+}
+
+#[test]
+fn filled_shapes_are_selected_from_inside() {
+    let mut annotations = Annotations::default();
+    let hollow = annotations.create(rect(Color::TRANSPARENT, 0.0));
+    annotations.insert(0, hollow);
+    assert_eq!(
+        selection::hit_test(&annotations, Point::new(50.0, 30.0), 2.0),
+        None
+    );
+
+    let mut annotations = Annotations::default();
+    let filled = annotations.create(rect(RED, 0.0));
+    let id = filled.id;
+    annotations.insert(0, filled);
+    assert_eq!(
+        selection::hit_test(&annotations, Point::new(50.0, 30.0), 2.0),
+        Some(id)
+    );
+}
+
+#[test]
+fn refill_is_undone_at_once() {
+    let mut session = session();
+    let id = session.add_annotation(rect(Color::TRANSPARENT, 0.0));
+
+    // e.g. while the color picker is dragged
+    session.refill_annotation(id, Color::rgba(255, 0, 0, 50), 2.0);
+    session.refill_annotation(id, Color::rgba(255, 0, 0, 100), 4.0);
+    assert_eq!(
+        session.annotations.get(id).unwrap().fill(),
+        Some((Color::rgba(255, 0, 0, 100), 4.0))
+    );
+
+    session.dispatch(Action::Undo).unwrap();
+    assert_eq!(
+        session.annotations.get(id).unwrap().fill(),
+        Some((Color::TRANSPARENT, 0.0))
+    );
+}
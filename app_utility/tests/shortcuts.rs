@@ -0,0 +1,9 @@
+use app_utility::core::shortcut::AllShortcuts;
+
+#[test]
+fn default_shortcuts_are_recognized() {
+    let mut shortcuts = AllShortcuts::default();
+    assert!(AllShortcuts::is_default(&shortcuts));
+    shortcuts.vec[0].active = !shortcuts.vec[0].active;
+    assert!(!AllShortcuts::is_default(&shortcuts));
+}
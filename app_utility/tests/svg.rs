@@ -0,0 +1,122 @@
+use app_utility::core::{
+    annotation::{ArrowHead, ArrowStyle, Color, Dash, Point, Redaction, Shape, Stroke},
+    session::Session,
+    text::TextStyle,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use image::Rgba;
+
+mod common;
+
+use common::{output_dir, session};
+
+fn save(session: &Session, name: &str) -> String {
+    let dir = output_dir();
+    let path = session.save_to(&dir.path(name)).unwrap();
+    std::fs::read_to_string(path).unwrap()
+}
+
+#[test]
+fn annotations_are_saved_as_vectors() {
+    let mut session = session();
+    session.add_annotation(Shape::Line {
+        start: Point::new(10.0, 10.0),
+        end: Point::new(150.0, 20.0),
+        stroke: Stroke::new(4.0, Color::rgb(255, 0, 0)),
+        dash: Dash::Dashed,
+    });
+    session.add_annotation(Shape::Arrow {
+        start: Point::new(10.0, 90.0),
+        end: Point::new(150.0, 50.0),
+        control: None,
+        stroke: Stroke::new(3.0, Color::rgb(0, 0, 255)),
+        dash: Dash::Solid,
+        style: ArrowStyle::default(),
+    });
+    session.add_annotation(Shape::Ellipse {
+        start: Point::new(20.0, 30.0),
+        end: Point::new(60.0, 70.0),
+        stroke: Stroke::new(2.0, Color::rgb(0, 128, 0)),
+        fill: Color::rgba(0, 128, 0, 64),
+    });
+    session.add_annotation(Shape::Text {
+        pos: Point::new(100.0, 60.0),
+        text: "<b> & co".to_string(),
+        style: TextStyle::default(),
+    });
+
+    let svg = save(&session, "vectors.svg");
+    assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="200" height="100""#));
+    assert!(svg.contains(r#"<path d="M10 10 L150 20""#));
+    assert!(svg.contains("stroke-dasharray="));
+    assert!(svg.contains(r#"<marker id="head1" markerUnits="userSpaceOnUse""#));
+    assert!(svg.contains(r#"marker-end="url(#head1)""#));
+    assert!(svg.contains(r#"<ellipse cx="40" cy="50" rx="20" ry="20""#));
+    assert!(svg.contains(r#"fill-opacity="0.2509804""#));
+    assert!(svg.contains("&lt;b&gt; &amp; co</text>"));
+    assert_eq!(svg.matches("@font-face").count(), 1);
+}
+
+#[test]
+fn redacted_pixels_are_not_in_the_file() {
+    let mut session = session();
+    session.add_annotation(Shape::Redact {
+        start: Point::new(20.0, 20.0),
+        end: Point::new(60.0, 50.0),
+        redaction: Redaction::Fill(Color::rgb(0, 0, 0)),
+    });
+
+    let svg = save(&session, "redacted.svg");
+    let images: Vec<_> = svg
+        .split(r#"href="data:image/png;base64,"#)
+        .skip(1)
+        .map(|data| {
+            let data = &data[..data.find('"').unwrap()];
+            image::load_from_memory(&STANDARD.decode(data).unwrap())
+                .unwrap()
+                .to_rgba8()
+        })
+        .collect();
+    // The screenshot with the redaction burned in, then the redaction where it is stacked
+    assert_eq!(images.len(), 2);
+    let screenshot = session.image.as_ref().unwrap();
+    assert_eq!(images[0].dimensions(), screenshot.dimensions());
+    assert_eq!(*images[0].get_pixel(30, 30), Rgba([0, 0, 0, 255]));
+    assert_eq!(images[0].get_pixel(100, 80), screenshot.get_pixel(100, 80));
+    assert_eq!(images[1].dimensions(), (40, 30));
+}
+
+#[test]
+fn arrow_heads_are_markers_of_the_line() {
+    let mut session = session();
+    session.add_annotation(Shape::Arrow {
+        start: Point::new(10.0, 50.0),
+        end: Point::new(110.0, 50.0),
+        control: None,
+        stroke: Stroke::new(4.0, Color::rgb(255, 0, 0)),
+        dash: Dash::Solid,
+        style: ArrowStyle {
+            head: ArrowHead::Filled,
+            both_ends: true,
+            size: 20.0,
+        },
+    });
+
+    let svg = save(&session, "heads.svg");
+    assert_eq!(svg.matches("<marker ").count(), 2);
+    assert_eq!(svg.matches("<polygon points=").count(), 2);
+    let line = svg.lines().find(|line| line.starts_with("<path ")).unwrap();
+    assert!(line.contains(r#"marker-end="url(#head1)""#));
+    assert!(line.contains(r#"marker-start="url(#head2)""#));
+    // The line stops inside of the filled heads, the markers still end on its tips
+    assert!(!line.contains("M10 50"));
+    assert!(svg.contains(" 110,50 "));
+    assert!(svg.contains(" 10,50 "));
+}
+
+#[test]
+fn an_empty_screenshot_is_a_single_image() {
+    let svg = save(&session(), "empty.svg");
+    assert_eq!(svg.matches("<image").count(), 1);
+    assert!(!svg.contains("<defs>"));
+}
@@ -0,0 +1,86 @@
+use app_utility::core::{
+    annotation::{Color, Point, Shape},
+    selection,
+    text::{self, Alignment, FontFamily, TextStyle},
+};
+
+mod common;
+
+use common::render;
+
+const WHITE: [u8; 4] = [255, 255, 255, 255];
+
+#[test]
+fn lines_are_aligned_inside_the_box() {
+    let text = "a long first line\nshort";
+    let left = text::layout(text, &TextStyle::default());
+    let center = text::layout(
+        text,
+        &TextStyle {
+            alignment: Alignment::Center,
+            ..TextStyle::default()
+        },
+    );
+    let right = text::layout(
+        text,
+        &TextStyle {
+            alignment: Alignment::Right,
+            ..TextStyle::default()
+        },
+    );
+
+    let padding = TextStyle::default().padding;
+    assert_eq!(left.size, center.size);
+    assert_eq!(left.lines[0].0, Point::new(padding, padding));
+    assert_eq!(left.lines[1].0.x, padding);
+    let gap = right.lines[1].0.x - padding;
+    assert!(gap > 0.0);
+    assert!((center.lines[1].0.x - padding - gap / 2.0).abs() < 1e-3);
+    assert!(left.lines[1].0.y > left.lines[0].0.y);
+}
+
+#[test]
+fn variants_use_their_own_font() {
+    let regular = TextStyle::default();
+    let bold = TextStyle {
+        bold: true,
+        ..regular
+    };
+    let mono_italic = TextStyle {
+        family: FontFamily::Mono,
+        italic: true,
+        ..regular
+    };
+    assert_eq!(regular.font_name(), "DejaVuSans");
+    assert_eq!(bold.font_name(), "DejaVuSans-Bold");
+    assert_eq!(mono_italic.font_name(), "DejaVuSansMono-Oblique");
+    assert!(text::layout("Bold", &bold).size.x > text::layout("Bold", &regular).size.x);
+}
+
+#[test]
+fn background_box_surrounds_the_text() {
+    let style = TextStyle {
+        size: 30.0,
+        color: Color::rgb(0, 0, 255),
+        background: Color::rgb(255, 255, 0),
+        padding: 10.0,
+        ..TextStyle::default()
+    };
+    let shape = Shape::Text {
+        pos: Point::new(20.0, 20.0),
+        text: "Hi".to_string(),
+        style,
+    };
+    let (min, max) = selection::bounds(&shape);
+    let image = render(shape, 300, 150);
+
+    assert_eq!(min, Point::new(20.0, 20.0));
+    assert_eq!(image.get_pixel(22, 22).0, [255, 255, 0, 255]);
+    assert_eq!(image.get_pixel(18, 22).0, WHITE);
+    assert_eq!(image.get_pixel(max.x as u32 + 2, max.y as u32 - 2).0, WHITE);
+    let text_pixels = image
+        .pixels()
+        .filter(|pixel| pixel.0[2] > 200 && pixel.0[0] < 50)
+        .count();
+    assert!(text_pixels > 20);
+}
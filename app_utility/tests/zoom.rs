@@ -0,0 +1,96 @@
+use app_utility::core::{
+    actions::Action,
+    annotation::Point,
+    zoom::{Zoom, MAX_SCALE},
+};
+
+mod common;
+
+use common::session;
+
+// A 4K screenshot in a viewer of 960x540 points, that fits it at a quarter of its size
+const IMAGE: Point = Point {
+    x: 3840.0,
+    y: 2160.0,
+};
+const VIEWER: Point = Point { x: 960.0, y: 540.0 };
+
+fn assert_near(a: Point, b: Point) {
+    assert!(a.distance(b) < 1e-2, "{:?} != {:?}", a, b);
+}
+
+#[test]
+fn zooming_keeps_the_pixel_under_the_pointer() {
+    let pointer = Point::new(300.0, 100.0);
+    let pixel = Zoom::Fit.to_image(pointer, IMAGE, VIEWER);
+    assert_near(pixel, Point::new(1200.0, 400.0));
+
+    let zoom = Zoom::Fit.zoomed_at(3.0, pointer, IMAGE, VIEWER);
+    assert_eq!(zoom.scale(IMAGE, VIEWER), 0.75);
+    assert_near(zoom.to_image(pointer, IMAGE, VIEWER), pixel);
+    let zoom = zoom.zoomed_at(2.0, Point::new(10.0, 500.0), IMAGE, VIEWER);
+    assert_near(
+        zoom.to_image(Point::new(10.0, 500.0), IMAGE, VIEWER),
+        Zoom::Fit.zoomed_at(3.0, pointer, IMAGE, VIEWER).to_image(
+            Point::new(10.0, 500.0),
+            IMAGE,
+            VIEWER,
+        ),
+    );
+}
+
+#[test]
+fn the_viewer_stays_inside_of_the_screenshot() {
+    let zoom = Zoom::Fit.zoomed_at(4.0, Point::new(480.0, 270.0), IMAGE, VIEWER);
+    assert_eq!(
+        zoom,
+        Zoom::Scaled {
+            scale: 1.0,
+            center: Point::new(1920.0, 1080.0)
+        }
+    );
+    // Dragging the screenshot far to the right shows its left edge
+    let zoom = zoom.panned(Point::new(10_000.0, -50.0), IMAGE, VIEWER);
+    assert_eq!(
+        zoom,
+        Zoom::Scaled {
+            scale: 1.0,
+            center: Point::new(480.0, 1130.0)
+        }
+    );
+    assert_near(zoom.origin(IMAGE, VIEWER), Point::new(0.0, -860.0));
+    // The whole screenshot fits, it cannot be moved
+    assert_eq!(
+        Zoom::Fit.panned(Point::new(10.0, 10.0), IMAGE, VIEWER),
+        Zoom::Fit
+    );
+}
+
+#[test]
+fn the_scale_is_limited() {
+    let pointer = Point::new(100.0, 100.0);
+    let zoom = Zoom::Fit.zoomed_at(8.0, pointer, IMAGE, VIEWER);
+    assert_eq!(zoom.zoomed_at(0.01, pointer, IMAGE, VIEWER), Zoom::Fit);
+    assert_eq!(
+        zoom.zoomed_at(1000.0, pointer, IMAGE, VIEWER)
+            .scale(IMAGE, VIEWER),
+        MAX_SCALE
+    );
+    // A small screenshot is enlarged to fit, it can still be shown smaller
+    let small = Point::new(96.0, 54.0);
+    let zoom = Zoom::Fit.zoomed_at(0.01, pointer, small, VIEWER);
+    assert_eq!(zoom.scale(small, VIEWER), 1.0);
+    assert_near(zoom.origin(small, VIEWER), Point::new(432.0, 243.0));
+}
+
+#[test]
+fn a_new_screenshot_fits_the_viewer() {
+    let mut session = session();
+    session.zoom = Zoom::Scaled {
+        scale: 4.0,
+        center: Point::new(20.0, 20.0),
+    };
+    session.dispatch(Action::Capture).unwrap();
+    session.capture().unwrap();
+    assert_eq!(session.zoom, Zoom::Fit);
+}